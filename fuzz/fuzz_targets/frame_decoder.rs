@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tcp_communication::{commands::parser::parse_command, server::codec::FrameDecoder};
+
+// Feeds the input in chunks sized by its first byte, the way reads arrive from a socket.
+fuzz_target!(|data: &[u8]| {
+    let Some((&chunk_size, bytes)) = data.split_first() else {
+        return;
+    };
+    let mut decoder = FrameDecoder::new(256);
+
+    for chunk in bytes.chunks(usize::from(chunk_size).max(1)) {
+        decoder.push(chunk);
+        while let Some(frame) = decoder.next_frame() {
+            if let Ok(frame) = frame {
+                let _ = parse_command(&frame);
+            }
+        }
+    }
+});
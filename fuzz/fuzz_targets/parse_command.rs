@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tcp_communication::commands::parser::parse_command;
+
+// Decoding arbitrary input must never panic, and anything that decodes must encode back to the
+// exact frame it was decoded from (the trailing newline is optional on the wire).
+fuzz_target!(|data: &[u8]| {
+    let Ok(frame) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(command) = parse_command(frame) {
+        let frame = frame.strip_suffix('\n').unwrap_or(frame);
+        assert_eq!(command.encode(), format!("{frame}\n"));
+        let _ = serde_json::to_string(&command);
+    }
+});
@@ -1,4 +1,9 @@
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ParseError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AlarmType {
     IllegalMovement,
     Falling,
@@ -9,7 +14,7 @@ pub enum AlarmType {
 }
 
 impl TryFrom<u8> for AlarmType {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -19,7 +24,20 @@ impl TryFrom<u8> for AlarmType {
             4 => Ok(AlarmType::LowPower),
             6 => Ok(AlarmType::LiftedUp),
             7 => Ok(AlarmType::IllegalDemolition),
-            _ => Err(format!("Invalid alarm type: {}", value)),
+            _ => Err(ParseError::bad_value("alarm type", value)),
+        }
+    }
+}
+
+impl From<&AlarmType> for u8 {
+    fn from(alarm_type: &AlarmType) -> u8 {
+        match alarm_type {
+            AlarmType::IllegalMovement => 1,
+            AlarmType::Falling => 2,
+            AlarmType::IllegalRemoval => 3,
+            AlarmType::LowPower => 4,
+            AlarmType::LiftedUp => 6,
+            AlarmType::IllegalDemolition => 7,
         }
     }
 }
@@ -1,4 +1,9 @@
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ParseError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BeepPlayContent {
     Hold,
     FindScooterAlert,
@@ -7,7 +12,7 @@ pub enum BeepPlayContent {
 }
 
 impl TryFrom<u8> for BeepPlayContent {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -15,7 +20,18 @@ impl TryFrom<u8> for BeepPlayContent {
             2 => Ok(BeepPlayContent::FindScooterAlert),
             80 => Ok(BeepPlayContent::TurnOffVoice),
             81 => Ok(BeepPlayContent::TurnOnVoice),
-            _ => Err(format!("Invalid beep play content: {}", value)),
+            _ => Err(ParseError::bad_value("beep play content", value)),
+        }
+    }
+}
+
+impl From<&BeepPlayContent> for u8 {
+    fn from(beep_play_content: &BeepPlayContent) -> u8 {
+        match beep_play_content {
+            BeepPlayContent::Hold => 1,
+            BeepPlayContent::FindScooterAlert => 2,
+            BeepPlayContent::TurnOffVoice => 80,
+            BeepPlayContent::TurnOnVoice => 81,
         }
     }
 }
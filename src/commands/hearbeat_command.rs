@@ -1,35 +1,59 @@
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ParseError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ScooterStatus {
     Unlocked,
     Locked,
 }
 
 impl TryFrom<u8> for ScooterStatus {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(ScooterStatus::Unlocked),
             1 => Ok(ScooterStatus::Locked),
-            _ => Err(format!("Invalid scooter status: {}", value)),
+            _ => Err(ParseError::bad_value("scooter status", value)),
+        }
+    }
+}
+
+impl From<&ScooterStatus> for u8 {
+    fn from(scooter_status: &ScooterStatus) -> u8 {
+        match scooter_status {
+            ScooterStatus::Unlocked => 0,
+            ScooterStatus::Locked => 1,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ChargingStatus {
     Uncharged,
     Charging,
 }
 
 impl TryFrom<u8> for ChargingStatus {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(ChargingStatus::Uncharged),
             1 => Ok(ChargingStatus::Charging),
-            _ => Err(format!("Invalid charging status: {}", value)),
+            _ => Err(ParseError::bad_value("charging status", value)),
+        }
+    }
+}
+
+impl From<&ChargingStatus> for u8 {
+    fn from(charging_status: &ChargingStatus) -> u8 {
+        match charging_status {
+            ChargingStatus::Uncharged => 0,
+            ChargingStatus::Charging => 1,
         }
     }
 }
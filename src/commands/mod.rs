@@ -5,5 +5,7 @@ pub mod parser;
 pub mod positioning_command;
 pub mod scooter_command;
 pub mod scooter_setting_command;
+pub mod status_command;
 pub mod tests;
+pub mod unlock_command;
 pub mod unlock_flow;
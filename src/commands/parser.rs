@@ -1,7 +1,9 @@
-use super::scooter_command::ScooterCommand;
-use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use super::scooter_command::{ScooterCommand, SCOOTER_HEADER, SERVER_HEADER};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc};
 use regex::Regex;
 
+use crate::errors::ParseError;
+
 #[derive(Debug)]
 pub struct ParsedCommand {
     pub imei: String,
@@ -9,47 +11,52 @@ pub struct ParsedCommand {
     pub additional_fields: Vec<String>,
 }
 
-pub fn parse_command(raw_data: &str) -> Result<ScooterCommand, String> {
-    let raw_data = raw_data.trim_end_matches("#\n");
+pub fn parse_command(raw_data: &str) -> Result<ScooterCommand, ParseError> {
+    let raw_data = raw_data.strip_suffix('\n').unwrap_or(raw_data);
+    let raw_data = raw_data
+        .strip_suffix('#')
+        .ok_or(ParseError::BadTerminator)?;
     let parts: Vec<&str> = raw_data.split(',').collect();
 
     // Validate header and vendor code
-    if parts.get(0) != Some(&"*SCOR") {
-        return Err(format!("Invalid header: {}", parts.get(0).unwrap_or(&"")));
+    if !matches!(parts.first(), Some(&SCOOTER_HEADER) | Some(&SERVER_HEADER)) {
+        return Err(ParseError::BadHeader(parts[0].to_string()));
     }
-    if parts.get(1) != Some(&"LZ") {
-        return Err(format!(
-            "Unsupported vendor code: {}",
-            parts.get(1).unwrap_or(&"")
-        ));
+    match parts.get(1) {
+        Some(&crate::config::VENDOR) => {}
+        vendor => return Err(ParseError::UnknownVendor(vendor.unwrap_or(&"").to_string())),
     }
 
     ScooterCommand::try_from(&parts[..])
 }
 
-pub fn parse_coordinates(value: &str, hemisphere: &str) -> Result<f64, String> {
+pub fn parse_coordinates(value: &str, hemisphere: &str) -> Result<f64, ParseError> {
     // Define regex for latitude and longitude formats
     let lat_regex = Regex::new(r"^\d{2}\d{2}\.\d{4}$").unwrap(); // ddmm.mmmm
     let lng_regex = Regex::new(r"^\d{3}\d{2}\.\d{4}$").unwrap(); // dddmm.mmmm
 
-    // Validate format based on expected input
-    if !lat_regex.is_match(value) && !lng_regex.is_match(value) {
-        return Err("Invalid coordinate format. Expected ddmm.mmmm or dddmm.mmmm.".to_string());
+    // Latitudes have two degree digits and longitudes three
+    let regex = match hemisphere {
+        "N" | "S" => &lat_regex,
+        _ => &lng_regex,
+    };
+    if !regex.is_match(value) {
+        return Err(ParseError::bad_value("coordinate", value));
     }
 
     // Ensure the input is a valid number
-    let value = value
+    let coordinate = value
         .trim()
         .parse::<f64>()
-        .map_err(|_| "Invalid coordinate format. Ensure it is a number.")?;
+        .map_err(|_| ParseError::bad_value("coordinate", value))?;
 
     // Split the value into degrees and minutes
-    let degrees = (value as i64 / 100) as f64; // Extract the degrees (integer part divided by 100)
-    let minutes = value % 100.0; // Extract the minutes (remainder of the division)
+    let degrees = (coordinate as i64 / 100) as f64; // Extract the degrees (integer part divided by 100)
+    let minutes = coordinate % 100.0; // Extract the minutes (remainder of the division)
 
     // Ensure minutes are within the valid range
     if !(0.0..60.0).contains(&minutes) {
-        return Err("Minutes should be in the range 0 to 59.999".to_string());
+        return Err(ParseError::bad_value("coordinate minutes", value));
     }
 
     // Calculate the WGS84 coordinate
@@ -59,11 +66,11 @@ pub fn parse_coordinates(value: &str, hemisphere: &str) -> Result<f64, String> {
     match hemisphere.trim() {
         "N" | "E" => Ok(coordinate),
         "S" | "W" => Ok(-coordinate),
-        _ => Err("Invalid hemisphere. Expected one of: N, S, E, W.".to_string()),
+        _ => Err(ParseError::bad_value("hemisphere", hemisphere)),
     }
 }
 
-pub fn parse_datetime(hhmmss: &str, ddmmyy: &str) -> Result<DateTime<Utc>, String> {
+pub fn parse_datetime(hhmmss: &str, ddmmyy: &str) -> Result<DateTime<Utc>, ParseError> {
     // Parse time (hhmmss)
     let time = parse_time(hhmmss)?;
 
@@ -74,47 +81,55 @@ pub fn parse_datetime(hhmmss: &str, ddmmyy: &str) -> Result<DateTime<Utc>, Strin
     Ok(Utc.from_utc_datetime(&NaiveDateTime::new(date, time)))
 }
 
-pub fn parse_time(hhmmss: &str) -> Result<NaiveTime, String> {
-    // Validate input length
-    if hhmmss.len() != 6 {
-        return Err("Invalid time format. Expected hhmmss (6 characters).".to_string());
+/// Parses the fractional seconds after the `.` in `hhmmss.ss`. An empty fraction is zero.
+pub fn parse_fraction(fraction: &str) -> Result<TimeDelta, ParseError> {
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ParseError::bad_value("fractional seconds", fraction));
+    }
+
+    // Only millisecond precision is kept
+    let millis = format!("{:0<3}", &fraction[..fraction.len().min(3)]);
+    let millis = millis.parse::<i64>().unwrap_or(0);
+    Ok(TimeDelta::milliseconds(millis))
+}
+
+pub fn parse_time(hhmmss: &str) -> Result<NaiveTime, ParseError> {
+    // Validate input length; ASCII only so the slices below fall on char boundaries
+    if hhmmss.len() != 6 || !hhmmss.is_ascii() {
+        return Err(ParseError::bad_value("time", hhmmss));
     }
 
     // Parse hours, minutes, and seconds
-    let hours = hhmmss[0..2]
-        .parse::<u32>()
-        .map_err(|_| "Invalid hours. Ensure it's a number.".to_string())?;
-    let minutes = hhmmss[2..4]
-        .parse::<u32>()
-        .map_err(|_| "Invalid minutes. Ensure it's a number.".to_string())?;
-    let seconds = hhmmss[4..6]
-        .parse::<u32>()
-        .map_err(|_| "Invalid seconds. Ensure it's a number.".to_string())?;
+    let hours = two_digits(&hhmmss[0..2], "hours")?;
+    let minutes = two_digits(&hhmmss[2..4], "minutes")?;
+    let seconds = two_digits(&hhmmss[4..6], "seconds")?;
 
     // Construct the NaiveTime, checking for out-of-range values
     NaiveTime::from_hms_opt(hours, minutes, seconds)
-        .ok_or_else(|| "Invalid time. Hours, minutes, or seconds out of range.".to_string())
+        .ok_or_else(|| ParseError::bad_value("time", hhmmss))
 }
 
-pub fn parse_date(ddmmyy: &str) -> Result<NaiveDate, String> {
-    // Ensure the input has exactly 6 characters
-    if ddmmyy.len() != 6 {
-        return Err("Invalid date format. Expected ddmmyy (6 characters).".to_string());
+pub fn parse_date(ddmmyy: &str) -> Result<NaiveDate, ParseError> {
+    // Ensure the input has exactly 6 ASCII characters
+    if ddmmyy.len() != 6 || !ddmmyy.is_ascii() {
+        return Err(ParseError::bad_value("date", ddmmyy));
     }
 
     // Parse day, month, and year
-    let day = ddmmyy[0..2]
-        .parse::<u32>()
-        .map_err(|_| "Invalid day. Ensure it's a number.".to_string())?;
-    let month = ddmmyy[2..4]
-        .parse::<u32>()
-        .map_err(|_| "Invalid month. Ensure it's a number.".to_string())?;
-    let year = 2000
-        + ddmmyy[4..6]
-            .parse::<i32>()
-            .map_err(|_| "Invalid year. Ensure it's a number.".to_string())?; // Assumes 21st century
+    let day = two_digits(&ddmmyy[0..2], "day")?;
+    let month = two_digits(&ddmmyy[2..4], "month")?;
+    let year = 2000 + two_digits(&ddmmyy[4..6], "year")? as i32; // Assumes 21st century
 
     // Construct the NaiveDate, checking for out-of-range values
-    NaiveDate::from_ymd_opt(year, month, day)
-        .ok_or_else(|| "Invalid date. Day, month, or year out of range.".to_string())
+    NaiveDate::from_ymd_opt(year, month, day).ok_or_else(|| ParseError::bad_value("date", ddmmyy))
+}
+
+/// Parses a two-digit date or time component. Signs are rejected, e.g. `+1`.
+fn two_digits(value: &str, field: &'static str) -> Result<u32, ParseError> {
+    if !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::bad_value(field, value));
+    }
+    value
+        .parse()
+        .map_err(|_| ParseError::bad_value(field, value))
 }
@@ -1,42 +1,66 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+use crate::errors::ParseError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PositioningIdentifier {
     ObtainPositioning,
     PositionTracking,
 }
 
 impl TryFrom<u8> for PositioningIdentifier {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(PositioningIdentifier::ObtainPositioning),
             1 => Ok(PositioningIdentifier::PositionTracking),
-            _ => Err(format!("Invalid positioning identifier: {}", value)),
+            _ => Err(ParseError::bad_value("positioning identifier", value)),
+        }
+    }
+}
+
+impl From<&PositioningIdentifier> for u8 {
+    fn from(positioning_identifier: &PositioningIdentifier) -> u8 {
+        match positioning_identifier {
+            PositioningIdentifier::ObtainPositioning => 0,
+            PositioningIdentifier::PositionTracking => 1,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PositioningStatus {
     Effective,
     Invalid,
 }
 
 impl TryFrom<char> for PositioningStatus {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
             'A' => Ok(PositioningStatus::Effective),
             'V' => Ok(PositioningStatus::Invalid),
-            _ => Err(format!("Invalid positioning status: {}", value)),
+            _ => Err(ParseError::bad_value("positioning status", value)),
+        }
+    }
+}
+
+impl From<&PositioningStatus> for char {
+    fn from(positioning_status: &PositioningStatus) -> char {
+        match positioning_status {
+            PositioningStatus::Effective => 'A',
+            PositioningStatus::Invalid => 'V',
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Hemisphere {
     North,
     South,
@@ -45,7 +69,7 @@ pub enum Hemisphere {
 }
 
 impl TryFrom<&str> for Hemisphere {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
@@ -53,12 +77,24 @@ impl TryFrom<&str> for Hemisphere {
             "S" => Ok(Hemisphere::South),
             "E" => Ok(Hemisphere::East),
             "W" => Ok(Hemisphere::West),
-            _ => Err(format!("Invalid hemisphere: {}", value)),
+            _ => Err(ParseError::bad_value("hemisphere", value)),
+        }
+    }
+}
+
+impl From<&Hemisphere> for &'static str {
+    fn from(hemisphere: &Hemisphere) -> &'static str {
+        match hemisphere {
+            Hemisphere::North => "N",
+            Hemisphere::South => "S",
+            Hemisphere::East => "E",
+            Hemisphere::West => "W",
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Mode {
     Autonomous,
     Differential,
@@ -67,7 +103,7 @@ pub enum Mode {
 }
 
 impl TryFrom<char> for Mode {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: char) -> Result<Self, Self::Error> {
         match value {
@@ -75,71 +111,87 @@ impl TryFrom<char> for Mode {
             'D' => Ok(Mode::Differential),
             'E' => Ok(Mode::Estimate),
             'N' => Ok(Mode::InvalidData),
-            _ => Err(format!("Invalid mode: {}", value)),
+            _ => Err(ParseError::bad_value("mode", value)),
+        }
+    }
+}
+
+impl From<&Mode> for char {
+    fn from(mode: &Mode) -> char {
+        match mode {
+            Mode::Autonomous => 'A',
+            Mode::Differential => 'D',
+            Mode::Estimate => 'E',
+            Mode::InvalidData => 'N',
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositioningResponse {
     pub imei: String,
     pub identifier: PositioningIdentifier,
+    /// Fix time, serialized as RFC 3339.
     pub utc_datetime: DateTime<Utc>,
     pub positioning_status: PositioningStatus,
+    /// WGS84 decimal degrees, negative in the southern hemisphere.
     pub latitude: f64,
     pub latitude_hemisphere: Hemisphere,
+    /// WGS84 decimal degrees, negative in the western hemisphere.
     pub longitude: f64,
     pub longitude_hemisphere: Hemisphere,
     pub satellites_number: u8,
+    /// Horizontal dilution of precision (HDOP).
     pub positioning_accuracy: f32,
+    /// Metres above sea level.
     pub altitude: f32,
     pub mode: Mode,
+    /// `positioning_accuracy` as received, e.g. `1.20`, so re-encoding keeps its precision.
+    #[serde(skip)]
+    pub positioning_accuracy_text: Option<String>,
+    /// `altitude` as received.
+    #[serde(skip)]
+    pub altitude_text: Option<String>,
 }
 
 impl PositioningResponse {
-    /// Converts latitude to WGS84 format.
-    pub fn convert_latitude_to_wgs84(&self) -> f64 {
-        let degrees = (self.latitude / 100.0).floor();
-        let minutes = self.latitude % 100.0;
-        let converted = degrees + (minutes / 60.0);
-        match self.latitude_hemisphere {
-            Hemisphere::North => converted,  // North is positive
-            Hemisphere::South => -converted, // South is negative
-            _ => panic!(
-                "Invalid hemisphere for latitude: {:?}",
-                self.latitude_hemisphere,
-            ),
-        }
-    }
-
-    /// Converts longitude to WGS84 format.
-    pub fn convert_longitude_to_wgs84(&self) -> f64 {
-        let degrees = (self.longitude / 100.0).floor();
-        let minutes = self.longitude % 100.0;
-        let converted = degrees + (minutes / 60.0);
-        match self.longitude_hemisphere {
-            Hemisphere::East => converted,  // East is positive
-            Hemisphere::West => -converted, // West is negative
-            _ => panic!(
-                "Invalid hemisphere for longitude: {:?}",
-                self.longitude_hemisphere,
+    /// Formats the D0 content fields the way the firmware sends them.
+    ///
+    /// Coordinates are written back in `ddmm.mmmm` / `dddmm.mmmm` and the UTC time as
+    /// `hhmmss.ss`, so decoding and re-encoding a D0 frame reproduces it.
+    pub fn encode_fields(&self) -> Vec<String> {
+        vec![
+            u8::from(&self.identifier).to_string(),
+            format!(
+                "{}.{:02}",
+                self.utc_datetime.format("%H%M%S"),
+                self.utc_datetime.timestamp_subsec_millis() / 10
             ),
-        }
+            char::from(&self.positioning_status).to_string(),
+            encode_coordinate(self.latitude, 2),
+            <&str>::from(&self.latitude_hemisphere).to_string(),
+            encode_coordinate(self.longitude, 3),
+            <&str>::from(&self.longitude_hemisphere).to_string(),
+            self.satellites_number.to_string(),
+            encode_decimal(self.positioning_accuracy, &self.positioning_accuracy_text),
+            self.utc_datetime.format("%d%m%y").to_string(),
+            encode_decimal(self.altitude, &self.altitude_text),
+            "M".to_string(),
+            char::from(&self.mode).to_string(),
+        ]
     }
 
     /// Provides a summary of the positioning in WGS84 format.
     pub fn positioning_summary(&self) -> String {
-        let wgs84_lat = self.convert_latitude_to_wgs84();
-        let wgs84_lon = self.convert_longitude_to_wgs84();
-
         format!(
             "IMEI: {}, WGS84 Coordinates: (Lat: {}, Lon: {}), Altitude: {}m, Status: {:?}, Mode: {:?}",
-            self.imei, wgs84_lat, wgs84_lon, self.altitude, self.positioning_status, self.mode
+            self.imei, self.latitude, self.longitude, self.altitude, self.positioning_status, self.mode
         )
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Status {
     Success,
     Failure,
@@ -147,14 +199,51 @@ pub enum Status {
 }
 
 impl TryFrom<u8> for Status {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(Status::Success),
             1 => Ok(Status::Failure),
             2 => Ok(Status::KeyError),
-            _ => Err(format!("Invalid status code: {}", value)),
+            _ => Err(ParseError::bad_value("status code", value)),
+        }
+    }
+}
+
+impl From<&Status> for u8 {
+    fn from(status: &Status) -> u8 {
+        match status {
+            Status::Success => 0,
+            Status::Failure => 1,
+            Status::KeyError => 2,
         }
     }
 }
+
+/// Converts signed decimal degrees back to the protocol's unsigned degrees-and-minutes form.
+///
+/// Rounding happens on whole ten-thousandths of a minute, so 59.99999 minutes carries into the
+/// degrees instead of printing as `60.0000`.
+fn encode_coordinate(coordinate: f64, degree_digits: usize) -> String {
+    const UNITS_PER_MINUTE: u64 = 10_000;
+    const UNITS_PER_DEGREE: u64 = 60 * UNITS_PER_MINUTE;
+
+    let units = (coordinate.abs() * UNITS_PER_DEGREE as f64).round() as u64;
+    let (degrees, minutes) = (units / UNITS_PER_DEGREE, units % UNITS_PER_DEGREE);
+    format!(
+        "{:0width$}{:02}.{:04}",
+        degrees,
+        minutes / UNITS_PER_MINUTE,
+        minutes % UNITS_PER_MINUTE,
+        width = degree_digits
+    )
+}
+
+/// The field text as received while it still matches `value`, the shortest form otherwise.
+fn encode_decimal(value: f32, text: &Option<String>) -> String {
+    match text {
+        Some(text) if text.parse::<f32>() == Ok(value) => text.clone(),
+        _ => value.to_string(),
+    }
+}
@@ -1,36 +1,56 @@
-use std::convert::TryFrom;
+use std::{convert::TryFrom, str::FromStr};
 
-use crate::server::commands::R0Operation;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ParseError;
 
 use super::{
     alarm_command::AlarmType,
     beep_command::BeepPlayContent,
     hearbeat_command::{ChargingStatus, ScooterStatus},
-    parser::{parse_coordinates, parse_datetime},
+    parser::{parse_coordinates, parse_datetime, parse_fraction},
     positioning_command::{Hemisphere, PositioningIdentifier, PositioningResponse, Status},
     scooter_setting_command::{HeadlightSwitch, ModeSetting, TaillightsFlashing, ThrottleResponse},
+    status_command::StatusReport,
+    unlock_command::R0Operation,
 };
 
-#[derive(Debug)]
+pub const SCOOTER_HEADER: &str = "*SCOR";
+pub const SERVER_HEADER: &str = "*SCOS";
+
+/// Every frame exchanged with a scooter, in either direction.
+///
+/// Frames sent by the scooter start with `*SCOR` and frames sent by the server with `*SCOS`.
+/// [`ScooterCommand::encode`] and `parse_command` are inverses of each other.
+///
+/// Serialized as a JSON object whose `type` field is the snake_case variant name, e.g.
+/// `{"type":"heart_beat","imei":"...","voltage":4.12,...}`. `user_id` on scooter replies is
+/// echoed verbatim from the request. Timestamps are Unix seconds on the wire and RFC 3339 in
+/// JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ScooterCommand {
+    // Scooter -> server (*SCOR)
     UnlockOrLockResponse {
         imei: String,
         operation: R0Operation,
         key: u8, // Randomly generated key
         user_id: String,
-        timestamp: String,
+        timestamp: DateTime<Utc>, // RFC 3339
     },
     UnlockResponse {
         imei: String,
         status: Status,
         user_id: String,
-        timestamp: String,
+        timestamp: DateTime<Utc>, // RFC 3339
     },
     LockResponse {
         imei: String,
         status: Status,
         user_id: String,
-        timestamp: String,
+        timestamp: DateTime<Utc>, // RFC 3339
+        /// Minutes ridden since the scooter was unlocked.
         cycling_time: u32,
     },
     PositioningResponse(PositioningResponse),
@@ -49,46 +69,317 @@ pub enum ScooterCommand {
         throttle_response: ThrottleResponse,
         taillights_flashing: TaillightsFlashing,
     },
+    StatusResponse(StatusReport),
     SigningIn {
         imei: String,
+        /// Battery voltage in volts.
         voltage: f32,
+        /// Battery level in percent.
         power: u8,
+        /// GSM signal strength, 0 to 31.
         signal: u8,
     },
+    /// Confirms the D1 tracking interval.
+    TrackingIntervalResponse {
+        imei: String,
+        /// Seconds between tracking uploads, 0 when tracking is off.
+        interval: u16,
+    },
     HeartBeat {
         imei: String,
         status: ScooterStatus,
+        /// Battery voltage in volts.
         voltage: f32,
+        /// GSM signal strength, 0 to 31.
         signal: u8,
+        /// Battery level in percent.
         power: u8,
         charging: ChargingStatus,
     },
+
+    // Server -> scooter (*SCOS)
+    UnlockOrLockRequest {
+        imei: String,
+        operation: R0Operation,
+        key_duration: u8, // Seconds the key stays valid
+        user_id: u32,
+        timestamp: DateTime<Utc>, // RFC 3339
+    },
+    UnlockRequest {
+        imei: String,
+        key: String, // Key from the R0 response
+        user_id: u32,
+        timestamp: DateTime<Utc>, // RFC 3339
+    },
+    LockRequest {
+        imei: String,
+        key: String, // Key from the R0 response
+    },
+    /// Asks the scooter for an S6 status report.
+    StatusRequest {
+        imei: String,
+    },
+    /// Asks the scooter for a single D0 position fix.
+    PositionRequest {
+        imei: String,
+    },
+    BeepRequest {
+        imei: String,
+        play_content: BeepPlayContent,
+    },
+    TrackingIntervalRequest {
+        imei: String,
+        /// Seconds between tracking uploads, 0 to turn tracking off.
+        interval: u16,
+    },
+    SettingRequest {
+        imei: String,
+        headlight_switch: HeadlightSwitch,
+        mode_setting: ModeSetting,
+        throttle_response: ThrottleResponse,
+        taillights_flashing: TaillightsFlashing,
+    },
+    /// Content-less reply confirming an inbound frame, e.g. `*SCOS,LZ,<imei>,H0#`.
+    Acknowledgement {
+        imei: String,
+        command: String,
+    },
+}
+
+/// Commands the server acknowledges with an empty frame of the same code.
+const ACKNOWLEDGED_COMMANDS: &[&str] = &["Q0", "H0", "W0", "L0", "L1"];
+
+impl ScooterCommand {
+    pub fn imei(&self) -> &str {
+        match self {
+            ScooterCommand::PositioningResponse(response) => &response.imei,
+            ScooterCommand::StatusResponse(report) => &report.imei,
+            ScooterCommand::UnlockOrLockResponse { imei, .. }
+            | ScooterCommand::UnlockResponse { imei, .. }
+            | ScooterCommand::LockResponse { imei, .. }
+            | ScooterCommand::AlarmCommand { imei, .. }
+            | ScooterCommand::BeepPlaybackCommand { imei, .. }
+            | ScooterCommand::ScooterSetting { imei, .. }
+            | ScooterCommand::SigningIn { imei, .. }
+            | ScooterCommand::HeartBeat { imei, .. }
+            | ScooterCommand::TrackingIntervalResponse { imei, .. }
+            | ScooterCommand::TrackingIntervalRequest { imei, .. }
+            | ScooterCommand::BeepRequest { imei, .. }
+            | ScooterCommand::StatusRequest { imei, .. }
+            | ScooterCommand::UnlockOrLockRequest { imei, .. }
+            | ScooterCommand::UnlockRequest { imei, .. }
+            | ScooterCommand::LockRequest { imei, .. }
+            | ScooterCommand::PositionRequest { imei }
+            | ScooterCommand::SettingRequest { imei, .. }
+            | ScooterCommand::Acknowledgement { imei, .. } => imei,
+        }
+    }
+
+    /// The protocol command code, e.g. `R0`.
+    pub fn code(&self) -> &str {
+        match self {
+            ScooterCommand::UnlockOrLockResponse { .. } => "R0",
+            ScooterCommand::UnlockResponse { .. } => "L0",
+            ScooterCommand::LockResponse { .. } => "L1",
+            ScooterCommand::PositioningResponse(_) => "D0",
+            ScooterCommand::StatusResponse(_) => "S6",
+            ScooterCommand::AlarmCommand { .. } => "W0",
+            ScooterCommand::BeepPlaybackCommand { .. } => "V0",
+            ScooterCommand::ScooterSetting { .. } => "S7",
+            ScooterCommand::SigningIn { .. } => "Q0",
+            ScooterCommand::HeartBeat { .. } => "H0",
+            ScooterCommand::TrackingIntervalResponse { .. } => "D1",
+            ScooterCommand::UnlockOrLockRequest { .. } => "R0",
+            ScooterCommand::UnlockRequest { .. } => "L0",
+            ScooterCommand::LockRequest { .. } => "L1",
+            ScooterCommand::PositionRequest { .. } => "D0",
+            ScooterCommand::TrackingIntervalRequest { .. } => "D1",
+            ScooterCommand::BeepRequest { .. } => "V0",
+            ScooterCommand::StatusRequest { .. } => "S6",
+            ScooterCommand::SettingRequest { .. } => "S7",
+            ScooterCommand::Acknowledgement { command, .. } => command,
+        }
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            ScooterCommand::UnlockOrLockRequest { .. }
+            | ScooterCommand::UnlockRequest { .. }
+            | ScooterCommand::LockRequest { .. }
+            | ScooterCommand::PositionRequest { .. }
+            | ScooterCommand::TrackingIntervalRequest { .. }
+            | ScooterCommand::BeepRequest { .. }
+            | ScooterCommand::StatusRequest { .. }
+            | ScooterCommand::SettingRequest { .. }
+            | ScooterCommand::Acknowledgement { .. } => SERVER_HEADER,
+            _ => SCOOTER_HEADER,
+        }
+    }
+
+    /// Encodes the command as a complete frame, without the reserved header.
+    pub fn encode(&self) -> String {
+        encode_frame(self.header(), self.imei(), self.code(), &self.content())
+    }
+
+    fn content(&self) -> Vec<String> {
+        match self {
+            ScooterCommand::UnlockOrLockResponse {
+                operation,
+                key,
+                user_id,
+                timestamp,
+                ..
+            } => vec![
+                <&str>::from(operation).to_string(),
+                key.to_string(),
+                user_id.clone(),
+                timestamp.timestamp().to_string(),
+            ],
+            ScooterCommand::UnlockResponse {
+                status,
+                user_id,
+                timestamp,
+                ..
+            } => vec![
+                u8::from(status).to_string(),
+                user_id.clone(),
+                timestamp.timestamp().to_string(),
+            ],
+            ScooterCommand::LockResponse {
+                status,
+                user_id,
+                timestamp,
+                cycling_time,
+                ..
+            } => vec![
+                u8::from(status).to_string(),
+                user_id.clone(),
+                timestamp.timestamp().to_string(),
+                cycling_time.to_string(),
+            ],
+            ScooterCommand::PositioningResponse(response) => response.encode_fields(),
+            ScooterCommand::StatusResponse(report) => report.encode_fields(),
+            ScooterCommand::AlarmCommand { alarm_type, .. } => {
+                vec![u8::from(alarm_type).to_string()]
+            }
+            ScooterCommand::BeepPlaybackCommand { play_content, .. }
+            | ScooterCommand::BeepRequest { play_content, .. } => {
+                vec![u8::from(play_content).to_string()]
+            }
+            ScooterCommand::ScooterSetting {
+                headlight_switch,
+                mode_setting,
+                throttle_response,
+                taillights_flashing,
+                ..
+            }
+            | ScooterCommand::SettingRequest {
+                headlight_switch,
+                mode_setting,
+                throttle_response,
+                taillights_flashing,
+                ..
+            } => vec![
+                u8::from(headlight_switch).to_string(),
+                u8::from(mode_setting).to_string(),
+                u8::from(throttle_response).to_string(),
+                u8::from(taillights_flashing).to_string(),
+            ],
+            ScooterCommand::SigningIn {
+                voltage,
+                power,
+                signal,
+                ..
+            } => vec![
+                encode_voltage(*voltage),
+                power.to_string(),
+                signal.to_string(),
+            ],
+            ScooterCommand::HeartBeat {
+                status,
+                voltage,
+                signal,
+                power,
+                charging,
+                ..
+            } => vec![
+                u8::from(status).to_string(),
+                encode_voltage(*voltage),
+                signal.to_string(),
+                power.to_string(),
+                u8::from(charging).to_string(),
+            ],
+            ScooterCommand::UnlockOrLockRequest {
+                operation,
+                key_duration,
+                user_id,
+                timestamp,
+                ..
+            } => vec![
+                <&str>::from(operation).to_string(),
+                key_duration.to_string(),
+                user_id.to_string(),
+                timestamp.timestamp().to_string(),
+            ],
+            ScooterCommand::UnlockRequest {
+                key,
+                user_id,
+                timestamp,
+                ..
+            } => vec![
+                key.clone(),
+                user_id.to_string(),
+                timestamp.timestamp().to_string(),
+            ],
+            ScooterCommand::LockRequest { key, .. } => vec![key.clone()],
+            ScooterCommand::TrackingIntervalResponse { interval, .. }
+            | ScooterCommand::TrackingIntervalRequest { interval, .. } => {
+                vec![interval.to_string()]
+            }
+            ScooterCommand::PositionRequest { .. }
+            | ScooterCommand::StatusRequest { .. }
+            | ScooterCommand::Acknowledgement { .. } => vec![],
+        }
+    }
+}
+
+/// Builds `<header>,<vendor>,<imei>,<code>[,<content>...]#\n`.
+pub fn encode_frame<S: AsRef<str>>(header: &str, imei: &str, code: &str, content: &[S]) -> String {
+    let mut frame = format!("{},{},{},{}", header, crate::config::VENDOR, imei, code);
+    for field in content {
+        frame.push(',');
+        frame.push_str(field.as_ref());
+    }
+    frame.push_str("#\n");
+    frame
+}
+
+/// Voltages are sent in units of 0.01V.
+fn encode_voltage(voltage: f32) -> String {
+    ((voltage * 100.0).round() as u32).to_string()
 }
 
 impl TryFrom<&[&str]> for ScooterCommand {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(parts: &[&str]) -> Result<Self, Self::Error> {
-        if parts.len() < 5 {
-            return Err("Invalid command format: insufficient parts".to_string());
+        if parts.first() == Some(&SERVER_HEADER) {
+            return decode_server_command(parts);
         }
 
+        require_fields(parts, 5)?;
+
         let imei = parts[2].to_string();
         let command = parts[3];
 
         match command {
             "R0" => {
-                if parts.len() < 8 {
-                    return Err("Invalid R0 response: insufficient parts".to_string());
-                }
+                require_exact_fields(parts, 8)?;
 
-                let operation: R0Operation =
-                    parts[4].try_into().map_err(|_| "Invalid R0 operation")?;
-                let key = parts[5]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid key field".to_string())?;
+                let operation: R0Operation = parts[4].try_into()?;
+                let key = parse_field(parts[5], "key")?;
                 let user_id = parts[6].to_string();
-                let timestamp = parts[7].to_string();
+                let timestamp = parse_timestamp(parts[7])?;
 
                 Ok(ScooterCommand::UnlockOrLockResponse {
                     imei,
@@ -99,16 +390,11 @@ impl TryFrom<&[&str]> for ScooterCommand {
                 })
             }
             "L0" => {
-                if parts.len() < 7 {
-                    return Err("Invalid L0 response: insufficient parts".to_string());
-                }
+                require_exact_fields(parts, 7)?;
 
-                let status = parts[4]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid L0 status field".to_string())?
-                    .try_into()?;
+                let status = parse_field::<u8>(parts[4], "status code")?.try_into()?;
                 let user_id = parts[5].to_string();
-                let timestamp = parts[6].to_string();
+                let timestamp = parse_timestamp(parts[6])?;
 
                 Ok(ScooterCommand::UnlockResponse {
                     imei,
@@ -118,19 +404,12 @@ impl TryFrom<&[&str]> for ScooterCommand {
                 })
             }
             "L1" => {
-                if parts.len() < 8 {
-                    return Err("Invalid L1 response: insufficient parts".to_string());
-                }
+                require_exact_fields(parts, 8)?;
 
-                let status = parts[4]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid L1 status field".to_string())?
-                    .try_into()?;
+                let status = parse_field::<u8>(parts[4], "status code")?.try_into()?;
                 let user_id = parts[5].to_string();
-                let timestamp = parts[6].to_string();
-                let cycling_time = parts[7]
-                    .parse::<u32>()
-                    .map_err(|_| "Invalid cycling time field".to_string())?;
+                let timestamp = parse_timestamp(parts[6])?;
+                let cycling_time = parse_field(parts[7], "cycling time")?;
 
                 Ok(ScooterCommand::LockResponse {
                     imei,
@@ -141,43 +420,41 @@ impl TryFrom<&[&str]> for ScooterCommand {
                 })
             }
             "D0" => {
-                if parts.len() < 17 {
-                    return Err("Invalid D0 response: insufficient parts".to_string());
-                }
+                require_exact_fields(parts, 17)?;
 
-                let identifier: PositioningIdentifier = parts[4]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid positioning identifier")?
-                    .try_into()?;
+                let identifier: PositioningIdentifier =
+                    parse_field::<u8>(parts[4], "positioning identifier")?.try_into()?;
 
-                let utc_time = parts[5].split('.').next().unwrap_or(&parts[5][0..5]);
-                let positioning_status = parts[6]
-                    .chars()
-                    .next()
-                    .ok_or("Invalid positioning status")?
-                    .try_into()?;
+                // hhmmss.ss, the only form the time is encoded in
+                let (utc_time, utc_fraction) = parts[5]
+                    .split_once('.')
+                    .filter(|(_, fraction)| fraction.len() == 2)
+                    .ok_or_else(|| ParseError::bad_value("time", parts[5]))?;
+                let positioning_status = single_char(parts[6], "positioning status")?.try_into()?;
 
+                if !matches!(parts[8], "N" | "S") {
+                    return Err(ParseError::bad_value("latitude hemisphere", parts[8]));
+                }
+                if !matches!(parts[10], "E" | "W") {
+                    return Err(ParseError::bad_value("longitude hemisphere", parts[10]));
+                }
                 let latitude = parse_coordinates(parts[7], parts[8])?;
                 let longitude = parse_coordinates(parts[9], parts[10])?;
 
-                let satellites_number = parts[11]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid satellites number")?;
-                let positioning_accuracy = parts[12]
-                    .parse::<f32>()
-                    .map_err(|_| "Invalid positioning accuracy")?;
+                let satellites_number = parse_field(parts[11], "satellites number")?;
+                let positioning_accuracy = parse_decimal(parts[12], "positioning accuracy")?;
 
-                let utc_date = parts[13].split('.').next().unwrap_or(&parts[5][0..5]);
-                let utc_datetime = parse_datetime(utc_time, utc_date)?;
+                let utc_datetime =
+                    parse_datetime(utc_time, parts[13])? + parse_fraction(utc_fraction)?;
 
-                let altitude = parts[14].parse::<f32>().map_err(|_| "Invalid altitude")?;
+                let altitude = parse_decimal(parts[14], "altitude")?;
 
                 // Validate height unit
                 if parts[15] != "M" {
-                    return Err(format!("Invalid height unit: {}", parts[15]));
+                    return Err(ParseError::bad_value("height unit", parts[15]));
                 }
 
-                let mode = parts[16].chars().next().ok_or("Invalid mode")?.try_into()?;
+                let mode = single_char(parts[16], "mode")?.try_into()?;
 
                 let latitude_hemisphere: Hemisphere = parts[8].try_into()?;
                 let longitude_hemisphere: Hemisphere = parts[10].try_into()?;
@@ -195,60 +472,38 @@ impl TryFrom<&[&str]> for ScooterCommand {
                     positioning_accuracy,
                     altitude,
                     mode,
+                    positioning_accuracy_text: Some(parts[12].to_string()),
+                    altitude_text: Some(parts[14].to_string()),
                 }))
             }
+            "D1" => {
+                require_exact_fields(parts, 5)?;
+
+                let interval = parse_field(parts[4], "tracking interval")?;
+
+                Ok(ScooterCommand::TrackingIntervalResponse { imei, interval })
+            }
             "W0" => {
-                if parts.len() < 5 {
-                    return Err("Invalid W0 response: insufficient parts".to_string());
-                }
+                require_exact_fields(parts, 5)?;
 
-                let imei = parts[2].to_string();
-                let alarm_type: AlarmType = parts[4]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid alarm type")?
-                    .try_into()?;
+                let alarm_type: AlarmType =
+                    parse_field::<u8>(parts[4], "alarm type")?.try_into()?;
 
                 Ok(ScooterCommand::AlarmCommand { imei, alarm_type })
             }
             "V0" => {
-                if parts.len() < 5 {
-                    return Err("Invalid V0 response: insufficient parts".to_string());
-                }
+                require_exact_fields(parts, 5)?;
 
-                let imei = parts[2].to_string();
-                let play_content: BeepPlayContent = parts[4]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid play content")?
-                    .try_into()?;
+                let play_content: BeepPlayContent =
+                    parse_field::<u8>(parts[4], "beep play content")?.try_into()?;
 
                 Ok(ScooterCommand::BeepPlaybackCommand { imei, play_content })
             }
             "S7" => {
-                if parts.len() < 8 {
-                    return Err("Invalid S7 response: insufficient parts".to_string());
-                }
-
-                let imei = parts[2].to_string();
-
-                let headlight_switch: HeadlightSwitch = parts[4]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid headlight switch value")?
-                    .try_into()?;
-
-                let mode_setting: ModeSetting = parts[5]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid mode setting value")?
-                    .try_into()?;
-
-                let throttle_response: ThrottleResponse = parts[6]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid throttle response value")?
-                    .try_into()?;
+                require_exact_fields(parts, 8)?;
 
-                let taillights_flashing: TaillightsFlashing = parts[7]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid taillights flashing value")?
-                    .try_into()?;
+                let (headlight_switch, mode_setting, throttle_response, taillights_flashing) =
+                    parse_settings(&parts[4..8])?;
 
                 Ok(ScooterCommand::ScooterSetting {
                     imei,
@@ -258,18 +513,35 @@ impl TryFrom<&[&str]> for ScooterCommand {
                     taillights_flashing,
                 })
             }
-            "Q0" => {
-                if parts.len() != 7 {
-                    return Err("Invalid Q0 response: insufficient parts".to_string());
+            "S6" => {
+                // Ride mileage is missing from older firmware
+                require_fields(parts, 12)?;
+                if parts.len() > 12 {
+                    require_exact_fields(parts, 13)?;
                 }
 
-                let imei = parts[2].to_string();
-                let voltage = parts[4]
-                    .parse::<f32>()
-                    .map(|v| v / 100.0)
-                    .map_err(|_| "Invalid voltage")?;
-                let power = parts[5].parse::<u8>().map_err(|_| "Invalid power")?;
-                let signal = parts[6].parse::<u8>().map_err(|_| "Invalid signal")?;
+                Ok(ScooterCommand::StatusResponse(StatusReport {
+                    imei,
+                    power: parse_field(parts[4], "power")?,
+                    mode_setting: parse_field::<u8>(parts[5], "mode setting")?.try_into()?,
+                    speed: parse_field(parts[6], "speed")?,
+                    charging: parse_field::<u8>(parts[7], "charging status")?.try_into()?,
+                    battery_voltage: parse_decivolts(parts[8])?,
+                    second_battery_voltage: parse_decivolts(parts[9])?,
+                    status: parse_field::<u8>(parts[10], "scooter status")?.try_into()?,
+                    signal: parse_field(parts[11], "signal")?,
+                    ride_mileage: parts
+                        .get(12)
+                        .map(|value| parse_mileage(value))
+                        .transpose()?,
+                }))
+            }
+            "Q0" => {
+                require_exact_fields(parts, 7)?;
+
+                let voltage = parse_voltage(parts[4])?;
+                let power = parse_field(parts[5], "power")?;
+                let signal = parse_field(parts[6], "signal")?;
 
                 Ok(ScooterCommand::SigningIn {
                     imei,
@@ -279,25 +551,15 @@ impl TryFrom<&[&str]> for ScooterCommand {
                 })
             }
             "H0" => {
-                if parts.len() != 9 {
-                    return Err("Invalid H0 response: insufficient parts".to_string());
-                }
+                require_exact_fields(parts, 9)?;
 
-                let imei = parts[2].to_string();
-                let status: ScooterStatus = parts[4]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid scooter status")?
-                    .try_into()?;
-                let voltage = parts[5]
-                    .parse::<f32>()
-                    .map(|v| v / 100.0)
-                    .map_err(|_| "Invalid voltage")?;
-                let signal = parts[6].parse::<u8>().map_err(|_| "Invalid signal")?;
-                let power = parts[7].parse::<u8>().map_err(|_| "Invalid power")?;
-                let charging: ChargingStatus = parts[8]
-                    .parse::<u8>()
-                    .map_err(|_| "Invalid charging status")?
-                    .try_into()?;
+                let status: ScooterStatus =
+                    parse_field::<u8>(parts[4], "scooter status")?.try_into()?;
+                let voltage = parse_voltage(parts[5])?;
+                let signal = parse_field(parts[6], "signal")?;
+                let power = parse_field(parts[7], "power")?;
+                let charging: ChargingStatus =
+                    parse_field::<u8>(parts[8], "charging status")?.try_into()?;
 
                 Ok(ScooterCommand::HeartBeat {
                     imei,
@@ -309,7 +571,191 @@ impl TryFrom<&[&str]> for ScooterCommand {
                 })
             }
 
-            _ => Err(format!("Unknown command: {}", command)),
+            _ => Err(ParseError::UnknownCommand(command.to_string())),
+        }
+    }
+}
+
+/// Decodes a `*SCOS` frame sent by the server.
+fn decode_server_command(parts: &[&str]) -> Result<ScooterCommand, ParseError> {
+    require_fields(parts, 4)?;
+
+    let imei = parts[2].to_string();
+    let command = parts[3];
+
+    if parts.len() == 4 && ACKNOWLEDGED_COMMANDS.contains(&command) {
+        return Ok(ScooterCommand::Acknowledgement {
+            imei,
+            command: command.to_string(),
+        });
+    }
+
+    match command {
+        "R0" => {
+            require_exact_fields(parts, 8)?;
+
+            let operation: R0Operation = parts[4].try_into()?;
+            let key_duration = parse_field(parts[5], "key duration")?;
+            let user_id = parse_field(parts[6], "user id")?;
+            let timestamp = parse_timestamp(parts[7])?;
+
+            Ok(ScooterCommand::UnlockOrLockRequest {
+                imei,
+                operation,
+                key_duration,
+                user_id,
+                timestamp,
+            })
+        }
+        "L0" => {
+            require_exact_fields(parts, 7)?;
+
+            let key = parts[4].to_string();
+            let user_id = parse_field(parts[5], "user id")?;
+            let timestamp = parse_timestamp(parts[6])?;
+
+            Ok(ScooterCommand::UnlockRequest {
+                imei,
+                key,
+                user_id,
+                timestamp,
+            })
+        }
+        "D0" => {
+            require_exact_fields(parts, 4)?;
+
+            Ok(ScooterCommand::PositionRequest { imei })
+        }
+        "S6" => {
+            require_exact_fields(parts, 4)?;
+
+            Ok(ScooterCommand::StatusRequest { imei })
+        }
+        "D1" => {
+            require_exact_fields(parts, 5)?;
+
+            let interval = parse_field(parts[4], "tracking interval")?;
+
+            Ok(ScooterCommand::TrackingIntervalRequest { imei, interval })
+        }
+        "V0" => {
+            require_exact_fields(parts, 5)?;
+
+            let play_content: BeepPlayContent =
+                parse_field::<u8>(parts[4], "beep play content")?.try_into()?;
+
+            Ok(ScooterCommand::BeepRequest { imei, play_content })
         }
+        "L1" => {
+            require_exact_fields(parts, 5)?;
+
+            Ok(ScooterCommand::LockRequest {
+                imei,
+                key: parts[4].to_string(),
+            })
+        }
+        "S7" => {
+            require_exact_fields(parts, 8)?;
+
+            let (headlight_switch, mode_setting, throttle_response, taillights_flashing) =
+                parse_settings(&parts[4..8])?;
+
+            Ok(ScooterCommand::SettingRequest {
+                imei,
+                headlight_switch,
+                mode_setting,
+                throttle_response,
+                taillights_flashing,
+            })
+        }
+        _ => Err(ParseError::UnknownCommand(command.to_string())),
+    }
+}
+
+/// Fails with [`ParseError::MissingField`] unless the frame has at least `count` fields.
+fn require_fields(parts: &[&str], count: usize) -> Result<(), ParseError> {
+    if parts.len() < count {
+        return Err(ParseError::MissingField {
+            command: parts.get(3).unwrap_or(&"").to_string(),
+            index: parts.len(),
+        });
+    }
+    Ok(())
+}
+
+fn require_exact_fields(parts: &[&str], count: usize) -> Result<(), ParseError> {
+    require_fields(parts, count)?;
+    match parts.get(count) {
+        Some(extra) => Err(ParseError::bad_value("trailing field", extra)),
+        None => Ok(()),
+    }
+}
+
+/// Parses a field that is encoded with `to_string`, rejecting other spellings of the same
+/// value, e.g. `080` or `+80`, which would not survive re-encoding.
+fn parse_field<T: FromStr + ToString>(value: &str, field: &'static str) -> Result<T, ParseError> {
+    value
+        .parse()
+        .ok()
+        .filter(|parsed: &T| parsed.to_string() == value)
+        .ok_or_else(|| ParseError::bad_value(field, value))
+}
+
+/// Parses Unix seconds.
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, ParseError> {
+    parse_field::<i64>(value, "timestamp").and_then(|seconds| {
+        DateTime::from_timestamp(seconds, 0)
+            .ok_or_else(|| ParseError::bad_value("timestamp", value))
+    })
+}
+
+/// Parses a decimal field whose text is kept for re-encoding. Only finite values are accepted.
+fn parse_decimal(value: &str, field: &'static str) -> Result<f32, ParseError> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|parsed| parsed.is_finite())
+        .ok_or_else(|| ParseError::bad_value(field, value))
+}
+
+fn single_char(value: &str, field: &'static str) -> Result<char, ParseError> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(ParseError::bad_value(field, value)),
     }
 }
+
+/// Inverse of [`encode_voltage`].
+fn parse_voltage(value: &str) -> Result<f32, ParseError> {
+    parse_field::<u16>(value, "voltage").map(|v| f32::from(v) / 100.0)
+}
+
+/// S6 voltages are sent in units of 0.1V.
+fn parse_decivolts(value: &str) -> Result<f32, ParseError> {
+    parse_field::<u16>(value, "voltage").map(|v| f32::from(v) / 10.0)
+}
+
+/// Ride mileage is sent in units of 10m.
+fn parse_mileage(value: &str) -> Result<u32, ParseError> {
+    parse_field::<u32>(value, "ride mileage")?
+        .checked_mul(10)
+        .ok_or_else(|| ParseError::bad_value("ride mileage", value))
+}
+
+type Settings = (
+    HeadlightSwitch,
+    ModeSetting,
+    ThrottleResponse,
+    TaillightsFlashing,
+);
+
+/// Parses the four S7 setting fields.
+fn parse_settings(fields: &[&str]) -> Result<Settings, ParseError> {
+    Ok((
+        parse_field::<u8>(fields[0], "headlight switch")?.try_into()?,
+        parse_field::<u8>(fields[1], "mode setting")?.try_into()?,
+        parse_field::<u8>(fields[2], "throttle response")?.try_into()?,
+        parse_field::<u8>(fields[3], "taillights flashing")?.try_into()?,
+    ))
+}
@@ -1,4 +1,9 @@
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ParseError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HeadlightSwitch {
     NoSet,
     Shutdown,
@@ -6,19 +11,30 @@ pub enum HeadlightSwitch {
 }
 
 impl TryFrom<u8> for HeadlightSwitch {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(HeadlightSwitch::NoSet),
             1 => Ok(HeadlightSwitch::Shutdown),
             2 => Ok(HeadlightSwitch::Open),
-            _ => Err(format!("Invalid headlight switch value: {}", value)),
+            _ => Err(ParseError::bad_value("headlight switch", value)),
         }
     }
 }
 
-#[derive(Debug)]
+impl From<&HeadlightSwitch> for u8 {
+    fn from(headlight_switch: &HeadlightSwitch) -> u8 {
+        match headlight_switch {
+            HeadlightSwitch::NoSet => 0,
+            HeadlightSwitch::Shutdown => 1,
+            HeadlightSwitch::Open => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ModeSetting {
     NoSet,
     LowSpeed,
@@ -27,7 +43,7 @@ pub enum ModeSetting {
 }
 
 impl TryFrom<u8> for ModeSetting {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -35,12 +51,24 @@ impl TryFrom<u8> for ModeSetting {
             1 => Ok(ModeSetting::LowSpeed),
             2 => Ok(ModeSetting::MediumSpeed),
             3 => Ok(ModeSetting::HighSpeed),
-            _ => Err(format!("Invalid mode setting value: {}", value)),
+            _ => Err(ParseError::bad_value("mode setting", value)),
+        }
+    }
+}
+
+impl From<&ModeSetting> for u8 {
+    fn from(mode_setting: &ModeSetting) -> u8 {
+        match mode_setting {
+            ModeSetting::NoSet => 0,
+            ModeSetting::LowSpeed => 1,
+            ModeSetting::MediumSpeed => 2,
+            ModeSetting::HighSpeed => 3,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ThrottleResponse {
     NoSet,
     Shutdown,
@@ -48,19 +76,30 @@ pub enum ThrottleResponse {
 }
 
 impl TryFrom<u8> for ThrottleResponse {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(ThrottleResponse::NoSet),
             1 => Ok(ThrottleResponse::Shutdown),
             2 => Ok(ThrottleResponse::Open),
-            _ => Err(format!("Invalid throttle response value: {}", value)),
+            _ => Err(ParseError::bad_value("throttle response", value)),
+        }
+    }
+}
+
+impl From<&ThrottleResponse> for u8 {
+    fn from(throttle_response: &ThrottleResponse) -> u8 {
+        match throttle_response {
+            ThrottleResponse::NoSet => 0,
+            ThrottleResponse::Shutdown => 1,
+            ThrottleResponse::Open => 2,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TaillightsFlashing {
     NoSet,
     Shutdown,
@@ -68,14 +107,24 @@ pub enum TaillightsFlashing {
 }
 
 impl TryFrom<u8> for TaillightsFlashing {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(TaillightsFlashing::NoSet),
             1 => Ok(TaillightsFlashing::Shutdown),
             2 => Ok(TaillightsFlashing::Open),
-            _ => Err(format!("Invalid taillights flashing value: {}", value)),
+            _ => Err(ParseError::bad_value("taillights flashing", value)),
+        }
+    }
+}
+
+impl From<&TaillightsFlashing> for u8 {
+    fn from(taillights_flashing: &TaillightsFlashing) -> u8 {
+        match taillights_flashing {
+            TaillightsFlashing::NoSet => 0,
+            TaillightsFlashing::Shutdown => 1,
+            TaillightsFlashing::Open => 2,
         }
     }
 }
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    hearbeat_command::{ChargingStatus, ScooterStatus},
+    scooter_setting_command::ModeSetting,
+};
+
+/// Live scooter status reported in an S6 frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusReport {
+    pub imei: String,
+    /// Battery level in percent.
+    pub power: u8,
+    pub mode_setting: ModeSetting,
+    /// Current speed in km/h.
+    pub speed: u16,
+    pub charging: ChargingStatus,
+    /// Battery 1 voltage in volts.
+    pub battery_voltage: f32,
+    /// Battery 2 voltage in volts, 0 when the scooter has no second battery.
+    pub second_battery_voltage: f32,
+    pub status: ScooterStatus,
+    /// Network signal strength, 2 to 32.
+    pub signal: u8,
+    /// Distance of the current ride in metres. Older firmware does not report it.
+    pub ride_mileage: Option<u32>,
+}
+
+impl StatusReport {
+    /// Formats the S6 content fields the way the firmware sends them.
+    pub fn encode_fields(&self) -> Vec<String> {
+        let mut fields = vec![
+            self.power.to_string(),
+            u8::from(&self.mode_setting).to_string(),
+            self.speed.to_string(),
+            u8::from(&self.charging).to_string(),
+            encode_decivolts(self.battery_voltage),
+            encode_decivolts(self.second_battery_voltage),
+            u8::from(&self.status).to_string(),
+            self.signal.to_string(),
+        ];
+        if let Some(ride_mileage) = self.ride_mileage {
+            // Sent in units of 10m
+            fields.push((ride_mileage / 10).to_string());
+        }
+        fields
+    }
+}
+
+/// S6 voltages are sent in units of 0.1V.
+fn encode_decivolts(voltage: f32) -> String {
+    ((voltage * 10.0).round() as u32).to_string()
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod alarm_command_tests {
     use crate::commands::alarm_command::AlarmType;
+    use crate::errors::ParseError;
 
     #[test]
     fn test_alarm_type_with_valid_illegal_movement() {
@@ -42,13 +43,13 @@ mod alarm_command_tests {
     fn test_alarm_type_with_invalid_value() {
         let result = AlarmType::try_from(0); // Invalid alarm type
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid alarm type: 0");
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("alarm type", 0));
     }
 
     #[test]
     fn test_alarm_type_with_invalid_high_value() {
         let result = AlarmType::try_from(8); // Invalid alarm type
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid alarm type: 8");
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("alarm type", 8));
     }
 }
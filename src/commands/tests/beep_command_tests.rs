@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod beep_command_tests {
     use crate::commands::beep_command::BeepPlayContent;
+    use crate::errors::ParseError;
 
     #[test]
     fn test_beep_play_content_with_valid_hold() {
@@ -30,13 +31,19 @@ mod beep_command_tests {
     fn test_beep_play_content_with_invalid_low_value() {
         let result = BeepPlayContent::try_from(0); // Invalid value
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid beep play content: 0");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("beep play content", 0)
+        );
     }
 
     #[test]
     fn test_beep_play_content_with_invalid_high_value() {
         let result = BeepPlayContent::try_from(100); // Invalid value
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid beep play content: 100");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("beep play content", 100)
+        );
     }
 }
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod hearbeat_command_tests {
     use crate::commands::hearbeat_command::{ChargingStatus, ScooterStatus};
+    use crate::errors::ParseError;
 
     // Tests for ScooterStatus
     #[test]
@@ -19,7 +20,10 @@ mod hearbeat_command_tests {
     fn test_scooter_status_with_invalid_value() {
         let result = ScooterStatus::try_from(2); // Invalid value
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid scooter status: 2");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("scooter status", 2)
+        );
     }
 
     // Tests for ChargingStatus
@@ -39,6 +43,9 @@ mod hearbeat_command_tests {
     fn test_charging_status_with_invalid_value() {
         let result = ChargingStatus::try_from(2); // Invalid value
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid charging status: 2");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("charging status", 2)
+        );
     }
 }
@@ -1,3 +1,5 @@
+#![allow(clippy::module_inception)]
+
 pub mod alarm_command_tests;
 pub mod beep_command_tests;
 pub mod hearbeat_command_tests;
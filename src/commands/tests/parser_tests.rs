@@ -1,10 +1,11 @@
 #[cfg(test)]
 mod parse_command_tests {
     use crate::commands::parser::parse_command;
+    use crate::errors::ParseError;
 
     #[test]
     fn test_parse_command_with_valid_data() {
-        let raw_data = "*SCOR,LZ,123456789012345,R0,1,2,User1,Timestamp#\n";
+        let raw_data = "*SCOR,LZ,123456789012345,R0,1,2,User1,1497689816#\n";
         let result = parse_command(raw_data);
         assert!(result.is_ok());
     }
@@ -15,6 +16,44 @@ mod parse_command_tests {
         let result = parse_command(raw_data);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_command_reports_typed_errors() {
+        let cases = [
+            (
+                "*INVALID,LZ,123456789012345,W0,1#\n",
+                ParseError::BadHeader("*INVALID".to_string()),
+            ),
+            (
+                "*SCOR,OM,123456789012345,W0,1#\n",
+                ParseError::UnknownVendor("OM".to_string()),
+            ),
+            ("*SCOR,LZ,123456789012345,W0,1\n", ParseError::BadTerminator),
+            (
+                "*SCOR,LZ,123456789012345,L1,0,1234,1497689816#\n",
+                ParseError::MissingField {
+                    command: "L1".to_string(),
+                    index: 7,
+                },
+            ),
+            (
+                "*SCOR,LZ,123456789012345,H0,1,abc,28,80,0#\n",
+                ParseError::bad_value("voltage", "abc"),
+            ),
+        ];
+
+        for (frame, expected) in cases {
+            let err = parse_command(frame).unwrap_err();
+            assert_eq!(err, expected, "{}", frame);
+        }
+    }
+
+    #[test]
+    fn test_parse_error_kind_and_message() {
+        let err = parse_command("*SCOR,LZ,123456789012345,ZZ,1#\n").unwrap_err();
+        assert_eq!(err.kind(), "unknown_command");
+        assert_eq!(err.to_string(), "Unknown command: ZZ");
+    }
 }
 
 #[cfg(test)]
@@ -67,6 +106,7 @@ mod parse_coordinates_tests {
 #[cfg(test)]
 mod parse_datetime_tests {
     use crate::commands::parser::parse_datetime;
+    use crate::errors::ParseError;
 
     #[test]
     fn test_parse_datetime_with_valid_input() {
@@ -78,26 +118,21 @@ mod parse_datetime_tests {
     fn test_parse_datetime_with_invalid_time() {
         let result = parse_datetime("250045", "151216"); // Invalid time
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid time. Hours, minutes, or seconds out of range."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("time", "250045"));
     }
 
     #[test]
     fn test_parse_datetime_with_invalid_date() {
         let result = parse_datetime("123045", "321216"); // Invalid date
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid date. Day, month, or year out of range."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("date", "321216"));
     }
 }
 
 #[cfg(test)]
 mod parse_time_tests {
     use crate::commands::parser::parse_time;
+    use crate::errors::ParseError;
 
     #[test]
     fn test_parse_time_with_valid_input() {
@@ -109,56 +144,42 @@ mod parse_time_tests {
     fn test_parse_time_with_short_input_length() {
         let result = parse_time("12304"); // Too short
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid time format. Expected hhmmss (6 characters)."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("time", "12304"));
     }
 
     #[test]
     fn test_parse_time_with_invalid_hours() {
         let result = parse_time("250045"); // Invalid hours
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid time. Hours, minutes, or seconds out of range."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("time", "250045"));
     }
 
     #[test]
     fn test_parse_time_with_invalid_minutes() {
         let result = parse_time("126045"); // Invalid minutes
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid time. Hours, minutes, or seconds out of range."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("time", "126045"));
     }
 
     #[test]
     fn test_parse_time_with_invalid_seconds() {
         let result = parse_time("123060"); // Invalid seconds
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid time. Hours, minutes, or seconds out of range."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("time", "123060"));
     }
 
     #[test]
     fn test_parse_time_with_non_numeric_characters() {
         let result = parse_time("12xx45"); // Non-numeric characters
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid minutes. Ensure it's a number."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("minutes", "xx"));
     }
 }
 
 #[cfg(test)]
 mod parse_date_tests {
     use crate::commands::parser::parse_date;
+    use crate::errors::ParseError;
 
     #[test]
     fn test_parse_date_with_valid_input() {
@@ -176,36 +197,99 @@ mod parse_date_tests {
     fn test_parse_date_with_short_input_length() {
         let result = parse_date("15121"); // Too short
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid date format. Expected ddmmyy (6 characters)."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("date", "15121"));
     }
 
     #[test]
     fn test_parse_date_with_invalid_day() {
         let result = parse_date("321216"); // Invalid day
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid date. Day, month, or year out of range."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("date", "321216"));
     }
 
     #[test]
     fn test_parse_date_with_invalid_month() {
         let result = parse_date("151316"); // Invalid month
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
-            "Invalid date. Day, month, or year out of range."
-        );
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("date", "151316"));
     }
 
     #[test]
     fn test_parse_date_with_non_numeric_characters() {
         let result = parse_date("abcd16"); // Non-numeric day and month
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid day. Ensure it's a number.");
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("day", "ab"));
+    }
+}
+
+#[cfg(test)]
+mod malformed_input_tests {
+    use crate::commands::parser::parse_command;
+    use crate::errors::ParseError;
+
+    const FRAMES: &[&str] = &[
+        "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n",
+        "*SCOR,LZ,123456789123456,H0,1,412,28,80,0#\n",
+        "*SCOR,LZ,123456789123456,L1,0,1234,1497689816,3#\n",
+        "*SCOS,LZ,123456789123456,S7,2,3,1,0#\n",
+    ];
+
+    #[test]
+    fn test_truncated_frames_do_not_panic() {
+        for frame in FRAMES {
+            for (end, _) in frame.char_indices() {
+                let _ = parse_command(&frame[..end]);
+                let _ = parse_command(&format!("{}#\n", &frame[..end]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_replaced_fields_do_not_panic_and_round_trip() {
+        let replacements = ["", "é", "ééé", "1", "-1", "NaN", "99999999999", "A", "X.Y"];
+        for frame in FRAMES {
+            let body = frame.trim_end_matches("#\n");
+            let fields: Vec<&str> = body.split(',').collect();
+            for index in 0..fields.len() {
+                for replacement in replacements {
+                    let mut mutated = fields.clone();
+                    mutated[index] = replacement;
+                    let mutated = format!("{}#\n", mutated.join(","));
+                    if let Ok(command) = parse_command(&mutated) {
+                        let encoded = command.encode();
+                        let decoded = parse_command(&encoded).unwrap();
+                        assert_eq!(decoded.encode(), encoded, "{}", mutated);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_short_positioning_time_is_rejected() {
+        let frame =
+            "*SCOR,LZ,123456789123456,D0,0,1,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n";
+        assert_eq!(
+            parse_command(frame).unwrap_err(),
+            ParseError::bad_value("time", "1")
+        );
+    }
+
+    #[test]
+    fn test_non_ascii_time_is_rejected() {
+        let frame = "*SCOR,LZ,123456789123456,D0,0,1éé0.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n";
+        assert!(matches!(
+            parse_command(frame),
+            Err(ParseError::BadFieldValue { field: "time", .. })
+        ));
+    }
+
+    #[test]
+    fn test_swapped_hemispheres_are_rejected() {
+        let frame = "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,E,11408.6214,N,6,0.21,151216,10,M,A#\n";
+        assert_eq!(
+            parse_command(frame).unwrap_err(),
+            ParseError::bad_value("latitude hemisphere", "E")
+        );
     }
 }
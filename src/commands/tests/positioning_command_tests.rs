@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod positioning_identifier_tests {
     use crate::commands::positioning_command::PositioningIdentifier;
+    use crate::errors::ParseError;
     use std::convert::TryFrom;
 
     #[test]
@@ -19,6 +20,9 @@ mod positioning_identifier_tests {
     fn test_positioning_identifier_with_invalid_value() {
         let result = PositioningIdentifier::try_from(2); // Invalid value
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid positioning identifier: 2");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("positioning identifier", 2)
+        );
     }
 }
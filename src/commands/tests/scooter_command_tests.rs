@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod operation_tests {
-    use crate::server::commands::R0Operation;
+    use crate::commands::unlock_command::R0Operation;
+    use crate::errors::ParseError;
 
     #[test]
     fn test_operation_with_valid_unlock() {
@@ -30,12 +31,13 @@ mod operation_tests {
     fn test_operation_with_invalid_value() {
         let result = R0Operation::try_from("4");
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid operation: 4");
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("operation", 4));
     }
 }
 
 #[cfg(test)]
 mod status_tests {
+    use crate::errors::ParseError;
     use std::convert::TryFrom;
 
     use crate::commands::positioning_command::Status;
@@ -62,13 +64,14 @@ mod status_tests {
     fn test_status_with_invalid_value() {
         let result = Status::try_from(3);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid status code: 3");
+        assert_eq!(result.unwrap_err(), ParseError::bad_value("status code", 3));
     }
 }
 
 #[cfg(test)]
 mod scooter_command_tests {
-    use crate::{commands::scooter_command::ScooterCommand, server::commands::R0Operation};
+    use crate::commands::{scooter_command::ScooterCommand, unlock_command::R0Operation};
+    use crate::errors::ParseError;
 
     use std::convert::TryFrom;
 
@@ -82,7 +85,7 @@ mod scooter_command_tests {
             "0",
             "255",
             "user1",
-            "timestamp",
+            "1497689816",
         ];
         let command = ScooterCommand::try_from(parts).unwrap();
         if let ScooterCommand::UnlockOrLockResponse {
@@ -97,7 +100,7 @@ mod scooter_command_tests {
             assert!(matches!(operation, R0Operation::Unlock));
             assert_eq!(key, 255);
             assert_eq!(user_id, "user1");
-            assert_eq!(timestamp, "timestamp");
+            assert_eq!(timestamp.to_rfc3339(), "2017-06-17T08:56:56+00:00");
         } else {
             panic!("Parsed command is not UnlockOrLockResponse");
         }
@@ -110,7 +113,10 @@ mod scooter_command_tests {
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err(),
-            "Invalid command format: insufficient parts"
+            ParseError::MissingField {
+                command: "Q0".to_string(),
+                index: 4
+            }
         );
     }
 
@@ -119,6 +125,292 @@ mod scooter_command_tests {
         let parts: &[&str] = &["*SCOR", "LZ", "123456789012345", "UNKNOWN", "0"];
         let result = ScooterCommand::try_from(parts);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Unknown command: UNKNOWN");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::UnknownCommand("UNKNOWN".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod encode_tests {
+    use crate::commands::{parser::parse_command, scooter_command::ScooterCommand};
+    use crate::errors::ParseError;
+
+    fn assert_roundtrip(frame: &str) {
+        let command = parse_command(frame).unwrap();
+        assert_eq!(command.encode(), frame);
+    }
+
+    #[test]
+    fn test_scooter_frames_roundtrip() {
+        for frame in [
+            "*SCOR,LZ,123456789123456,Q0,412,80,28#\n",
+            "*SCOR,LZ,123456789123456,H0,1,412,28,80,0#\n",
+            "*SCOR,LZ,123456789123456,W0,1#\n",
+            "*SCOR,LZ,123456789123456,V0,2#\n",
+            "*SCOR,LZ,123456789123456,D1,60#\n",
+            "*SCOR,LZ,123456789123456,R0,0,55,1234,1497689816#\n",
+            "*SCOR,LZ,123456789123456,L0,0,1234,1497689816#\n",
+            "*SCOR,LZ,123456789123456,L1,0,1234,1497689816,3#\n",
+            "*SCOR,LZ,123456789123456,S7,0,3,0,0#\n",
+            "*SCOR,LZ,123456789123456,S6,80,3,22,0,372,372,0,28#\n",
+            "*SCOR,LZ,123456789123456,S6,80,1,15,1,372,0,0,28,100#\n",
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n",
+        ] {
+            assert_roundtrip(frame);
+        }
+    }
+
+    #[test]
+    fn test_server_frames_roundtrip() {
+        for frame in [
+            "*SCOS,LZ,123456789123456,R0,0,20,1234,1497689816#\n",
+            "*SCOS,LZ,123456789123456,L0,55,1234,1497689816#\n",
+            "*SCOS,LZ,123456789123456,L1,55#\n",
+            "*SCOS,LZ,123456789123456,D0#\n",
+            "*SCOS,LZ,123456789123456,D1,60#\n",
+            "*SCOS,LZ,123456789123456,S6#\n",
+            "*SCOS,LZ,123456789123456,V0,2#\n",
+            "*SCOS,LZ,123456789123456,S7,2,3,1,0#\n",
+            "*SCOS,LZ,123456789123456,Q0#\n",
+            "*SCOS,LZ,123456789123456,H0#\n",
+            "*SCOS,LZ,123456789123456,W0#\n",
+        ] {
+            assert_roundtrip(frame);
+        }
+    }
+
+    #[test]
+    fn test_device_samples_encode_byte_exact() {
+        // Frames in each shape the firmware sends, including the protocol document examples
+        for frame in [
+            "*SCOR,LZ,123456789123456,Q0,412,80,28#\n",
+            "*SCOR,LZ,868351070531393,Q0,3805,100,31#\n",
+            "*SCOR,LZ,123456789123456,H0,0,412,28,80,0#\n",
+            "*SCOR,LZ,868351070531393,H0,1,3805,31,100,1#\n",
+            "*SCOR,LZ,123456789123456,R0,0,55,1234,1497689816#\n",
+            "*SCOR,LZ,123456789123456,L0,0,1234,1497689816#\n",
+            "*SCOR,LZ,123456789123456,L1,0,1234,1497689816,3#\n",
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n",
+            "*SCOR,LZ,868351070531393,D0,1,083015.50,A,5222.1835,N,00454.0521,W,11,0.9,020324,-3.6,M,D#\n",
+            "*SCOR,LZ,123456789123456,D0,0,000000.00,V,0000.0000,N,00000.0000,E,0,0,010100,0,M,N#\n",
+            "*SCOR,LZ,123456789123456,D1,60#\n",
+            "*SCOR,LZ,123456789123456,W0,2#\n",
+            "*SCOR,LZ,123456789123456,V0,1#\n",
+            "*SCOR,LZ,123456789123456,S6,80,3,22,0,372,372,0,28#\n",
+            "*SCOR,LZ,123456789123456,S6,100,1,0,1,420,0,1,31,0#\n",
+            "*SCOR,LZ,123456789123456,S7,0,3,0,0#\n",
+        ] {
+            let command = parse_command(frame).unwrap();
+            assert_eq!(command.encode().as_bytes(), frame.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_frames_that_would_not_reencode_are_rejected() {
+        for frame in [
+            // Trailing fields
+            "*SCOR,LZ,123456789123456,R0,0,55,1234,1497689816,9#\n",
+            "*SCOR,LZ,123456789123456,L0,0,1234,1497689816,9#\n",
+            "*SCOR,LZ,123456789123456,L1,0,1234,1497689816,3,9#\n",
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A,9#\n",
+            "*SCOR,LZ,123456789123456,D1,60,9#\n",
+            "*SCOR,LZ,123456789123456,W0,1,9#\n",
+            "*SCOR,LZ,123456789123456,V0,2,9#\n",
+            "*SCOR,LZ,123456789123456,S7,0,3,0,0,9#\n",
+            // Multi-character status and mode
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,AV,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n",
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,AD#\n",
+            // Time without its fraction, or a date with one
+            "*SCOR,LZ,123456789123456,D0,0,124458,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n",
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216.00,10,M,A#\n",
+            "*SCOR,LZ,123456789123456,D0,0,+12458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n",
+            // A longitude-shaped latitude
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,02237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n",
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,nan,151216,10,M,A#\n",
+            // Voltages that are not a plain integer
+            "*SCOR,LZ,123456789123456,Q0,0412,80,28#\n",
+            "*SCOR,LZ,123456789123456,Q0,412.5,80,28#\n",
+            "*SCOR,LZ,123456789123456,Q0,nan,80,28#\n",
+            "*SCOR,LZ,123456789123456,Q0,-1,80,28#\n",
+            "*SCOR,LZ,123456789123456,Q0,1e40,80,28#\n",
+            "*SCOR,LZ,123456789123456,H0,1,+412,28,80,0#\n",
+            "*SCOR,LZ,123456789123456,S6,80,3,22,0,37.2,0,0,28#\n",
+            "*SCOR,LZ,123456789123456,S6,80,3,22,0,372,inf,0,28#\n",
+        ] {
+            assert!(parse_command(frame).is_err(), "{}", frame);
+        }
+    }
+
+    #[test]
+    fn test_server_acknowledgement_is_decoded() {
+        let command = parse_command("*SCOS,LZ,123456789123456,H0#\n").unwrap();
+        assert!(matches!(
+            command,
+            ScooterCommand::Acknowledgement { ref command, .. } if command == "H0"
+        ));
+        assert_eq!(command.code(), "H0");
+        assert_eq!(command.imei(), "123456789123456");
+    }
+
+    #[test]
+    fn test_positioning_keeps_fractional_seconds() {
+        let frame = "*SCOR,LZ,123456789123456,D0,0,124458.50,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n";
+        let ScooterCommand::PositioningResponse(response) = parse_command(frame).unwrap() else {
+            panic!("Expected a positioning response");
+        };
+        assert_eq!(
+            response.utc_datetime.to_rfc3339(),
+            "2016-12-15T12:44:58.500+00:00"
+        );
+        assert_eq!(
+            ScooterCommand::PositioningResponse(response).encode(),
+            frame
+        );
+    }
+
+    #[test]
+    fn test_positioning_keeps_decimal_precision() {
+        for frame in [
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,9,1.20,151216,10.0,M,A#\n",
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2259.9999,N,11400.0000,E,4,2.5,151216,152.50,M,D#\n",
+        ] {
+            assert_roundtrip(frame);
+        }
+    }
+
+    #[test]
+    fn test_positioning_coordinates_never_encode_sixty_minutes() {
+        let frame = "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n";
+        let ScooterCommand::PositioningResponse(mut response) = parse_command(frame).unwrap()
+        else {
+            panic!("Expected a positioning response");
+        };
+        response.latitude = 22.999_999_999;
+        response.longitude = 113.999_999_999;
+        response.altitude = 12.5;
+
+        assert_eq!(
+            ScooterCommand::PositioningResponse(response).encode(),
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2300.0000,N,11400.0000,E,6,0.21,151216,12.5,M,A#\n"
+        );
+    }
+
+    #[test]
+    fn test_status_report_is_decoded_with_units() {
+        let frame = "*SCOR,LZ,123456789123456,S6,80,3,22,0,372,0,0,28,125#\n";
+        let ScooterCommand::StatusResponse(report) = parse_command(frame).unwrap() else {
+            panic!("Expected a status response");
+        };
+        assert_eq!(report.speed, 22);
+        assert_eq!(report.battery_voltage, 37.2);
+        assert_eq!(report.second_battery_voltage, 0.0);
+        assert_eq!(report.ride_mileage, Some(1250));
+    }
+
+    #[test]
+    fn test_status_report_with_overflowing_mileage() {
+        let result =
+            parse_command("*SCOR,LZ,123456789123456,S6,80,3,22,0,372,0,0,28,4294967295#\n");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("ride mileage", "4294967295")
+        );
+    }
+
+    #[test]
+    fn test_server_frame_with_unknown_command() {
+        let result = parse_command("*SCOS,LZ,123456789123456,X9,1#\n");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::UnknownCommand("X9".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod serialization_tests {
+    use serde_json::json;
+
+    use crate::{
+        commands::{parser::parse_command, scooter_command::ScooterCommand},
+        server::events::{DeviceEvent, DisconnectReason},
+    };
+
+    #[test]
+    fn test_heartbeat_is_serialized_with_units() {
+        let command = parse_command("*SCOR,LZ,123456789123456,H0,1,412,28,80,1#\n").unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&command).unwrap(),
+            json!({
+                "type": "heart_beat",
+                "imei": "123456789123456",
+                "status": "locked",
+                "voltage": 4.12f32,
+                "signal": 28,
+                "power": 80,
+                "charging": "charging",
+            })
+        );
+    }
+
+    #[test]
+    fn test_positioning_is_serialized_as_decimal_degrees_and_rfc3339() {
+        let command = parse_command(
+            "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,S,11408.6214,W,6,0.21,151216,10,M,A#\n",
+        )
+        .unwrap();
+        let value = serde_json::to_value(&command).unwrap();
+
+        assert_eq!(value["type"], "positioning_response");
+        assert_eq!(value["utc_datetime"], "2016-12-15T12:44:58Z");
+        assert!((value["latitude"].as_f64().unwrap() + 22.62919).abs() < 1e-5);
+        assert!((value["longitude"].as_f64().unwrap() + 114.14369).abs() < 1e-5);
+        assert_eq!(value["latitude_hemisphere"], "south");
+        assert_eq!(value["mode"], "autonomous");
+    }
+
+    #[test]
+    fn test_timestamps_are_serialized_as_rfc3339() {
+        for frame in [
+            "*SCOR,LZ,123456789123456,L1,0,1234,1497689816,3#\n",
+            "*SCOS,LZ,123456789123456,L0,55,1234,1497689816#\n",
+        ] {
+            let command = parse_command(frame).unwrap();
+            let value = serde_json::to_value(&command).unwrap();
+
+            assert_eq!(value["timestamp"], "2017-06-17T08:56:56Z", "{}", frame);
+            assert_eq!(command.encode(), frame);
+        }
+    }
+
+    #[test]
+    fn test_commands_deserialize_from_their_json() {
+        let frame = "*SCOR,LZ,123456789123456,R0,0,55,1234,1497689816#\n";
+        let command = parse_command(frame).unwrap();
+
+        let json = serde_json::to_string(&command).unwrap();
+        let decoded: ScooterCommand = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.encode(), frame);
+        assert!(json.contains(r#""operation":"unlock""#));
+    }
+
+    #[test]
+    fn test_device_events_are_tagged() {
+        let event = DeviceEvent::Disconnected {
+            imei: "123456789123456".to_string(),
+            reason: DisconnectReason::IdleTimeout,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "event": "disconnected",
+                "imei": "123456789123456",
+                "reason": "idle_timeout",
+            })
+        );
     }
 }
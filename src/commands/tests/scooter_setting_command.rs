@@ -3,6 +3,7 @@ mod scooter_setting_command_tests {
     use crate::commands::scooter_setting_command::{
         HeadlightSwitch, ModeSetting, TaillightsFlashing, ThrottleResponse,
     };
+    use crate::errors::ParseError;
 
     #[test]
     fn test_headlight_switch_with_valid_values() {
@@ -20,7 +21,10 @@ mod scooter_setting_command_tests {
     fn test_headlight_switch_with_invalid_value() {
         let result = HeadlightSwitch::try_from(3);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid headlight switch value: 3");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("headlight switch", 3)
+        );
     }
 
     #[test]
@@ -42,7 +46,10 @@ mod scooter_setting_command_tests {
     fn test_mode_setting_with_invalid_value() {
         let result = ModeSetting::try_from(4);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid mode setting value: 4");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("mode setting", 4)
+        );
     }
 
     #[test]
@@ -61,7 +68,10 @@ mod scooter_setting_command_tests {
     fn test_throttle_response_with_invalid_value() {
         let result = ThrottleResponse::try_from(3);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid throttle response value: 3");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("throttle response", 3)
+        );
     }
 
     #[test]
@@ -81,6 +91,9 @@ mod scooter_setting_command_tests {
         let try_from = TaillightsFlashing::try_from(3);
         let result = try_from;
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid taillights flashing value: 3");
+        assert_eq!(
+            result.unwrap_err(),
+            ParseError::bad_value("taillights flashing", 3)
+        );
     }
 }
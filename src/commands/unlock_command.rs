@@ -0,0 +1,40 @@
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ParseError;
+
+/// What an R0 request asks the scooter to prepare a key for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum R0Operation {
+    Unlock,
+    Lock,
+    RFIDCardUnlock,
+    RFIDCardLock,
+}
+
+impl From<&R0Operation> for &'static str {
+    fn from(operation: &R0Operation) -> Self {
+        match operation {
+            R0Operation::Unlock => "0",
+            R0Operation::Lock => "1",
+            R0Operation::RFIDCardUnlock => "2",
+            R0Operation::RFIDCardLock => "3",
+        }
+    }
+}
+
+impl TryFrom<&str> for R0Operation {
+    type Error = ParseError;
+
+    fn try_from(operation_string: &str) -> Result<Self, Self::Error> {
+        match operation_string {
+            "0" => Ok(R0Operation::Unlock),
+            "1" => Ok(R0Operation::Lock),
+            "2" => Ok(R0Operation::RFIDCardUnlock),
+            "3" => Ok(R0Operation::RFIDCardLock),
+            _ => Err(ParseError::bad_value("operation", operation_string)),
+        }
+    }
+}
@@ -1,3 +1,168 @@
 pub const SERVER_ADDRESS: &str = "0.0.0.0:8124";
+pub const PARSER_ADDRESS: &str = "127.0.0.1:5000";
 pub const VENDOR: &str = "LZ";
 pub const USER_ID: u32 = 1;
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+pub const MAX_FRAME_SIZE: usize = 1024;
+
+pub const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 10;
+
+/// How long to wait for a scooter to answer `command`.
+///
+/// Defaults can be overridden per command with `<COMMAND>_TIMEOUT_SECS`, e.g. `R0_TIMEOUT_SECS=5`.
+pub fn command_timeout(command: &str) -> std::time::Duration {
+    let default = match command {
+        "L0" | "L1" => 20,
+        "D0" => 60, // Getting a GPS fix can take a while
+        _ => DEFAULT_COMMAND_TIMEOUT_SECS,
+    };
+    env_secs(&format!("{}_TIMEOUT_SECS", command), default)
+}
+
+pub const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 900;
+
+/// How long a scooter may stay silent before its connection is dropped.
+///
+/// Override with `IDLE_TIMEOUT_SECS`. This should cover several heartbeat intervals.
+pub fn idle_timeout() -> std::time::Duration {
+    env_secs("IDLE_TIMEOUT_SECS", DEFAULT_IDLE_TIMEOUT_SECS)
+}
+
+pub const DEFAULT_WRITE_TIMEOUT_SECS: u64 = 10;
+
+/// How long a write to a scooter may take before the connection is considered stalled and
+/// dropped. Override with `WRITE_TIMEOUT_SECS`.
+pub fn write_timeout() -> std::time::Duration {
+    env_secs("WRITE_TIMEOUT_SECS", DEFAULT_WRITE_TIMEOUT_SECS)
+}
+
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 240;
+pub const DEFAULT_HEARTBEAT_GRACE_SECS: u64 = 30;
+pub const HEARTBEAT_MISSED_BEFORE_OFFLINE: u32 = 3;
+pub const PRESENCE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often scooters are expected to send an H0 heartbeat. Override with
+/// `HEARTBEAT_INTERVAL_SECS`.
+pub fn heartbeat_interval() -> std::time::Duration {
+    env_secs("HEARTBEAT_INTERVAL_SECS", DEFAULT_HEARTBEAT_INTERVAL_SECS)
+}
+
+/// Extra time allowed for a late heartbeat before it counts as missed. Override with
+/// `HEARTBEAT_GRACE_SECS`.
+pub fn heartbeat_grace() -> std::time::Duration {
+    env_secs("HEARTBEAT_GRACE_SECS", DEFAULT_HEARTBEAT_GRACE_SECS)
+}
+
+/// D1 tracking upload interval in seconds while a scooter is locked, from
+/// `TRACKING_LOCKED_SECS`. When unset the firmware's own interval is left alone.
+pub fn tracking_locked_interval() -> Option<u16> {
+    env_value("TRACKING_LOCKED_SECS")
+}
+
+/// D1 tracking upload interval in seconds during a ride, from `TRACKING_RIDING_SECS`.
+pub fn tracking_riding_interval() -> Option<u16> {
+    env_value("TRACKING_RIDING_SECS")
+}
+
+pub const DEFAULT_ALARM_WINDOW_SECS: u64 = 300;
+/// Alarms kept per scooter in its device state.
+pub const ALARM_HISTORY_SIZE: usize = 20;
+
+/// How long repeats of the same alarm are suppressed after it is raised. Override with
+/// `ALARM_WINDOW_SECS`.
+pub fn alarm_window() -> std::time::Duration {
+    env_secs("ALARM_WINDOW_SECS", DEFAULT_ALARM_WINDOW_SECS)
+}
+
+pub const DEFAULT_WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+pub const DEFAULT_WEBHOOK_RETRY_BASE_MS: u64 = 1000;
+pub const WEBHOOK_REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// Events waiting for delivery per webhook; further events are dead-lettered.
+pub const WEBHOOK_QUEUE_SIZE: usize = 256;
+/// Failed webhook deliveries kept for inspection.
+pub const DEAD_LETTER_CAPACITY: usize = 1000;
+
+/// Attempts per webhook delivery before it is dead-lettered. Override with
+/// `WEBHOOK_MAX_ATTEMPTS`.
+pub fn webhook_max_attempts() -> u32 {
+    env_value("WEBHOOK_MAX_ATTEMPTS")
+        .filter(|attempts| *attempts > 0)
+        .unwrap_or(DEFAULT_WEBHOOK_MAX_ATTEMPTS)
+}
+
+/// Delay before the first webhook retry, doubled for each further one. Override with
+/// `WEBHOOK_RETRY_BASE_MS`.
+pub fn webhook_retry_base_delay() -> std::time::Duration {
+    std::time::Duration::from_millis(
+        env_value("WEBHOOK_RETRY_BASE_MS").unwrap_or(DEFAULT_WEBHOOK_RETRY_BASE_MS),
+    )
+}
+
+pub const MQTT_DEFAULT_PORT: u16 = 1883;
+pub const MQTT_QUEUE_CAPACITY: usize = 256;
+pub const MQTT_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Broker for the MQTT bridge from `MQTT_HOST` and `MQTT_PORT`. The bridge only runs when
+/// the `mqtt` feature is enabled and `MQTT_HOST` is set.
+pub fn mqtt_broker() -> Option<(String, u16)> {
+    let host = std::env::var("MQTT_HOST")
+        .ok()
+        .filter(|host| !host.is_empty())?;
+    Some((host, env_value("MQTT_PORT").unwrap_or(MQTT_DEFAULT_PORT)))
+}
+
+/// How often riding scooters are polled with S6 for live speed and mileage, from
+/// `STATUS_POLL_SECS`. Polling is off when unset.
+pub fn status_poll_interval() -> Option<std::time::Duration> {
+    env_value::<u64>("STATUS_POLL_SECS")
+        .filter(|seconds| *seconds > 0)
+        .map(std::time::Duration::from_secs)
+}
+
+pub const DEFAULT_HISTORY_RETENTION_DAYS: u64 = 30;
+pub const HISTORY_PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+/// Time range of a history query that does not give its start.
+pub const HISTORY_DEFAULT_RANGE: std::time::Duration = std::time::Duration::from_secs(24 * 3600);
+/// Records returned by a history query unless it asks for fewer.
+pub const HISTORY_QUERY_LIMIT: u32 = 1000;
+pub const HISTORY_MAX_QUERY_LIMIT: u32 = 10_000;
+/// Writes waiting for the history database before new ones are dropped.
+pub const HISTORY_WRITE_QUEUE_SIZE: usize = 10_000;
+
+/// SQLite file for the telemetry history, from `HISTORY_DB_PATH`. History is not recorded
+/// when unset.
+pub fn history_db_path() -> Option<String> {
+    std::env::var("HISTORY_DB_PATH")
+        .ok()
+        .filter(|path| !path.is_empty())
+}
+
+/// How long telemetry history is kept. Override with `HISTORY_RETENTION_DAYS`.
+pub fn history_retention() -> std::time::Duration {
+    std::time::Duration::from_secs(
+        env_value::<u64>("HISTORY_RETENTION_DAYS")
+            .unwrap_or(DEFAULT_HISTORY_RETENTION_DAYS)
+            .saturating_mul(24 * 3600),
+    )
+}
+
+/// Fleet-wide S7 setting `setting` as its protocol value, from `DESIRED_<SETTING>`, e.g.
+/// `DESIRED_MODE_SETTING=1` for low speed. When unset the firmware default is left alone.
+pub fn desired_setting(setting: &str) -> Option<u8> {
+    env_value(&format!("DESIRED_{}", setting))
+}
+
+fn env_value<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+}
+
+/// Reads a duration in whole seconds from the environment variable `name`.
+fn env_secs(name: &str, default: u64) -> std::time::Duration {
+    let seconds = std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default);
+    std::time::Duration::from_secs(seconds)
+}
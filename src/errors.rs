@@ -1,3 +1,4 @@
+use axum::http::StatusCode;
 use std::fmt;
 
 #[derive(Debug)]
@@ -5,6 +6,18 @@ pub enum AppError {
     InvalidCommand(String),
     ClientNotFound(String),
     SocketError(String),
+    DeviceTimeout(String),
+}
+
+impl AppError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::InvalidCommand(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::ClientNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::SocketError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::DeviceTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
 }
 
 impl fmt::Display for AppError {
@@ -13,8 +26,78 @@ impl fmt::Display for AppError {
             AppError::InvalidCommand(msg) => write!(f, "Invalid command: {}", msg),
             AppError::ClientNotFound(msg) => write!(f, "Client not found: {}", msg),
             AppError::SocketError(msg) => write!(f, "Socket error: {}", msg),
+            AppError::DeviceTimeout(msg) => write!(f, "Device did not respond: {}", msg),
         }
     }
 }
 
 impl std::error::Error for AppError {}
+
+/// Why a device frame could not be decoded.
+///
+/// Fields are indexed from the header, so in `*SCOR,LZ,<imei>,R0,<operation>,...` the operation
+/// is field 4.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The frame does not start with `*SCOR` or `*SCOS`.
+    BadHeader(String),
+    UnknownVendor(String),
+    UnknownCommand(String),
+    /// The frame does not end with `#`.
+    BadTerminator,
+    MissingField {
+        command: String,
+        index: usize,
+    },
+    BadFieldValue {
+        field: &'static str,
+        value: String,
+    },
+    /// The frame decoded but is not the reply that was expected.
+    UnexpectedContent {
+        command: String,
+        frame: String,
+    },
+}
+
+impl ParseError {
+    pub fn bad_value(field: &'static str, value: impl ToString) -> Self {
+        ParseError::BadFieldValue {
+            field,
+            value: value.to_string(),
+        }
+    }
+
+    /// A stable snake_case name for the variant, for metrics and structured output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParseError::BadHeader(_) => "bad_header",
+            ParseError::UnknownVendor(_) => "unknown_vendor",
+            ParseError::UnknownCommand(_) => "unknown_command",
+            ParseError::BadTerminator => "bad_terminator",
+            ParseError::MissingField { .. } => "missing_field",
+            ParseError::BadFieldValue { .. } => "bad_field_value",
+            ParseError::UnexpectedContent { .. } => "unexpected_content",
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::BadHeader(header) => write!(f, "Invalid header: {}", header),
+            ParseError::UnknownVendor(vendor) => write!(f, "Unsupported vendor code: {}", vendor),
+            ParseError::UnknownCommand(command) => write!(f, "Unknown command: {}", command),
+            ParseError::BadTerminator => write!(f, "Frame is not terminated by '#'"),
+            ParseError::MissingField { command, index } => {
+                write!(f, "Invalid {} frame: missing field {}", command, index)
+            }
+            ParseError::BadFieldValue { field, value } => write!(f, "Invalid {}: {}", field, value),
+            ParseError::UnexpectedContent { command, frame } => {
+                write!(f, "Unexpected {} response: {}", command, frame.trim_end())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
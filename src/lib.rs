@@ -0,0 +1,6 @@
+pub mod commands;
+pub mod config;
+pub mod errors;
+pub mod logs;
+pub mod server;
+pub mod utils;
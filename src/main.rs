@@ -1,63 +1,89 @@
-use axum::{routing::post, Router};
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
-use tokio::{
-    io::AsyncReadExt,
-    net::{TcpListener, TcpStream},
-    sync::Mutex,
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
 };
-
-use server::{
-    change_gear_handler::change_gear_handler, change_headlight_handler::change_headlight_handler,
-    lock_handler::lock_handler, start_server, unlock_handler::unlock_handler,
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+use tcp_communication::{
+    config, logs,
+    server::{
+        beep_handler::beep_handler,
+        change_gear_handler::change_gear_handler,
+        change_headlight_handler::change_headlight_handler,
+        connectivity_handler::connectivity_handler,
+        desired_settings_handler::{clear_desired_settings_handler, desired_settings_handler},
+        device_handler::device_handler,
+        event_stream_handler::{sse_handler, ws_handler},
+        history,
+        history_handler::{
+            alarm_history_handler, command_history_handler, heartbeat_history_handler,
+            position_history_handler,
+        },
+        list_devices_handler::list_devices_handler,
+        locate_handler::locate_handler,
+        lock_handler::lock_handler,
+        parser_service::start_parser_server,
+        presence,
+        settings_handler::settings_handler,
+        start_server,
+        status_handler::status_handler,
+        status_poll,
+        tracking_handler::tracking_handler,
+        unlock_handler::unlock_handler,
+        webhooks,
+        webhooks_handler::{
+            create_webhook_handler, dead_letters_handler, delete_webhook_handler,
+            list_webhooks_handler,
+        },
+        AppState,
+    },
 };
 
-pub mod commands;
-pub mod config;
-pub mod errors;
-pub mod logs;
-pub mod server;
-pub mod utils;
-
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
     logs::init();
 
-    let clients: Arc<Mutex<HashMap<String, Arc<Mutex<TcpStream>>>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+    let state = AppState::new();
 
     // Start TCP server for main functionality
-    let tcp_clients_main = clients.clone();
+    let tcp_state_main = state.clone();
     tokio::spawn(async move {
-        if let Err(e) = start_server(config::SERVER_ADDRESS, tcp_clients_main).await {
+        if let Err(e) = start_server(config::SERVER_ADDRESS, tcp_state_main).await {
             eprintln!("Error in TCP server: {}", e);
         }
     });
 
-    // Start second TCP listener for parsing
-    let tcp_clients_parser = clients.clone();
-    tokio::spawn(async move {
-        let parser_address = "127.0.0.1:5000"; // Change as needed
-        let parser_listener = TcpListener::bind(parser_address)
-            .await
-            .expect("Failed to bind parser listener");
+    // Track heartbeat-driven online/offline state
+    tokio::spawn(presence::monitor(state.clone()));
 
-        println!("Parser server listening on {}", parser_address);
+    // Deliver device events to registered webhooks
+    tokio::spawn(webhooks::run(state.clone()));
+
+    // Bridge events and commands to MQTT
+    #[cfg(feature = "mqtt")]
+    if let Some((host, port)) = config::mqtt_broker() {
+        tokio::spawn(tcp_communication::server::mqtt::run(
+            state.clone(),
+            host,
+            port,
+        ));
+    }
+
+    // Drop telemetry history past its retention period
+    if let Some(history) = state.history.clone() {
+        tokio::spawn(history::run_retention(history, config::history_retention()));
+    }
+
+    // Poll riding scooters for live speed and mileage
+    if let Some(interval) = config::status_poll_interval() {
+        tokio::spawn(status_poll::run(state.clone(), interval));
+    }
 
-        loop {
-            match parser_listener.accept().await {
-                Ok((stream, addr)) => {
-                    println!("New parser connection from {}", addr);
-                    let tcp_clients = tcp_clients_parser.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = handle_parser_connection(stream, tcp_clients).await {
-                            eprintln!("Error handling parser connection: {}", e);
-                        }
-                    });
-                }
-                Err(e) => {
-                    eprintln!("Error accepting parser connection: {}", e);
-                }
-            }
+    // Start second TCP listener for decoding captured device frames
+    tokio::spawn(async move {
+        if let Err(e) = start_parser_server(config::PARSER_ADDRESS).await {
+            eprintln!("Error in parser server: {}", e);
         }
     });
 
@@ -67,7 +93,40 @@ async fn main() -> std::io::Result<()> {
         .route("/lock", post(lock_handler))
         .route("/change-gear", post(change_gear_handler))
         .route("/change-headlight", post(change_headlight_handler))
-        .with_state(clients);
+        .route("/locate", post(locate_handler))
+        .route("/beep", post(beep_handler))
+        .route("/query-status", post(status_handler))
+        .route("/devices", get(list_devices_handler))
+        .route("/devices/:imei", get(device_handler))
+        .route("/devices/:imei/connectivity", get(connectivity_handler))
+        .route("/devices/:imei/tracking", put(tracking_handler))
+        .route("/devices/:imei/settings", put(settings_handler))
+        .route(
+            "/devices/:imei/settings/desired",
+            put(desired_settings_handler).delete(clear_desired_settings_handler),
+        )
+        .route(
+            "/devices/:imei/history/heartbeats",
+            get(heartbeat_history_handler),
+        )
+        .route(
+            "/devices/:imei/history/positions",
+            get(position_history_handler),
+        )
+        .route("/devices/:imei/history/alarms", get(alarm_history_handler))
+        .route(
+            "/devices/:imei/history/commands",
+            get(command_history_handler),
+        )
+        .route("/events", get(sse_handler))
+        .route("/events/ws", get(ws_handler))
+        .route(
+            "/webhooks",
+            get(list_webhooks_handler).post(create_webhook_handler),
+        )
+        .route("/webhooks/dead-letters", get(dead_letters_handler))
+        .route("/webhooks/:id", delete(delete_webhook_handler))
+        .with_state(state);
 
     let listen_addr = SocketAddr::from(([0, 0, 0, 0], 4000));
     let listener = TcpListener::bind(listen_addr).await?;
@@ -75,30 +134,5 @@ async fn main() -> std::io::Result<()> {
 
     axum::serve(listener, app.into_make_service())
         .await
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
-}
-
-// Function to handle parser connections
-async fn handle_parser_connection(
-    stream: TcpStream,
-    clients: Arc<Mutex<HashMap<String, Arc<Mutex<TcpStream>>>>>,
-) -> Result<(), std::io::Error> {
-    let mut buf = [0; 1024];
-    let mut stream = stream;
-
-    loop {
-        let n = stream.read(&mut buf).await?;
-        if n == 0 {
-            break; // Connection closed
-        }
-
-        // Process the received data
-        let received_data = String::from_utf8_lossy(&buf[..n]);
-        println!("Received parser data: {}", received_data);
-
-        // Implement parsing logic here
-        // Optionally interact with `clients` if necessary
-    }
-
-    Ok(())
+        .map_err(std::io::Error::other)
 }
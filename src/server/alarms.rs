@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::alarm_command::AlarmType;
+
+use super::{
+    events::{self, DeviceEvent},
+    registry::{DeviceRegistry, DeviceState},
+    AppState,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    /// Theft or tampering.
+    Critical,
+}
+
+impl From<&AlarmType> for Severity {
+    fn from(alarm_type: &AlarmType) -> Self {
+        match alarm_type {
+            AlarmType::IllegalRemoval | AlarmType::IllegalDemolition => Severity::Critical,
+            AlarmType::IllegalMovement | AlarmType::LiftedUp | AlarmType::Falling => {
+                Severity::Warning
+            }
+            AlarmType::LowPower => Severity::Info,
+        }
+    }
+}
+
+/// A W0 alarm together with the repeats folded into it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alarm {
+    pub alarm_type: AlarmType,
+    pub severity: Severity,
+    pub raised_at: DateTime<Utc>,    // RFC 3339
+    pub last_seen_at: DateTime<Utc>, // RFC 3339
+    /// W0 frames of the same type received within the deduplication window after `raised_at`.
+    pub repeats: u32,
+}
+
+/// Stores an alarm reported by `imei` and returns it if it is new.
+///
+/// An alarm of the same type raised less than `window` ago absorbs the report as a repeat
+/// instead, so a scooter that keeps reporting the same alarm raises it once per window.
+pub fn record(
+    registry: &DeviceRegistry,
+    imei: &str,
+    alarm_type: &AlarmType,
+    window: Duration,
+    now: DateTime<Utc>,
+) -> Option<Alarm> {
+    let mut registry = registry.lock().unwrap();
+    let state = registry
+        .entry(imei.to_string())
+        .or_insert_with(|| DeviceState::new(now));
+    state.updated_at = now;
+
+    let previous = state
+        .alarms
+        .iter_mut()
+        .rev()
+        .find(|alarm| alarm.alarm_type == *alarm_type);
+    if let Some(alarm) = previous {
+        if (now - alarm.raised_at).to_std().unwrap_or_default() < window {
+            alarm.repeats += 1;
+            alarm.last_seen_at = now;
+            return None;
+        }
+    }
+
+    let alarm = Alarm {
+        alarm_type: alarm_type.clone(),
+        severity: alarm_type.into(),
+        raised_at: now,
+        last_seen_at: now,
+        repeats: 0,
+    };
+    state.alarms.push(alarm.clone());
+    let excess = state
+        .alarms
+        .len()
+        .saturating_sub(crate::config::ALARM_HISTORY_SIZE);
+    state.alarms.drain(..excess);
+    Some(alarm)
+}
+
+/// Records the alarm and publishes an `Alarm` event unless it is a repeat.
+pub fn raise(state: &AppState, imei: &str, alarm_type: &AlarmType, now: DateTime<Utc>) {
+    let Some(alarm) = record(&state.registry, imei, alarm_type, state.alarm_window, now) else {
+        return;
+    };
+
+    println!(
+        "Alarm from {}: {:?} ({:?})",
+        imei, alarm.alarm_type, alarm.severity
+    );
+    events::publish(
+        &state.events,
+        DeviceEvent::Alarm {
+            imei: imei.to_string(),
+            alarm,
+        },
+    );
+}
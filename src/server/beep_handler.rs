@@ -0,0 +1,67 @@
+use crate::{
+    commands::beep_command::BeepPlayContent,
+    server::ClientMap,
+    server::{commands, handler::*},
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct BeepRequest {
+    pub imei: String,
+    /// One of `hold`, `find_scooter_alert`, `turn_off_voice` or `turn_on_voice`.
+    pub play_content: BeepPlayContent,
+}
+
+#[derive(Serialize)]
+pub struct BeepResponse {
+    pub success: bool,
+    pub message: String,
+    pub imei: String,
+}
+
+pub async fn beep_handler(
+    State(clients): State<ClientMap>,
+    Json(payload): Json<BeepRequest>,
+) -> impl IntoResponse {
+    let imei = payload.imei.clone();
+
+    let connection = match get_client(&clients, &imei).await {
+        Ok(connection) => connection,
+        Err(err) => {
+            return (
+                err.status_code(),
+                Json(BeepResponse {
+                    success: false,
+                    message: err.to_string(),
+                    imei,
+                }),
+            );
+        }
+    };
+
+    let v0_command = commands::generate_v0_command(&imei, &payload.play_content);
+    if let Err(err) = request_v0(&connection, &v0_command, &imei, &payload.play_content).await {
+        return (
+            err.status_code(),
+            Json(BeepResponse {
+                success: false,
+                message: err.to_string(),
+                imei,
+            }),
+        );
+    }
+
+    (
+        StatusCode::OK,
+        Json(BeepResponse {
+            success: true,
+            message: format!("Scooter played {:?}", payload.play_content),
+            imei,
+        }),
+    )
+}
@@ -1,7 +1,4 @@
-use crate::{
-    server::ClientMap,
-    server::{commands, handler::*},
-};
+use crate::{server::handler::*, server::ClientMap};
 use axum::{
     extract::{Json, State},
     http::StatusCode,
@@ -30,14 +27,14 @@ pub async fn change_gear_handler(
 ) -> impl IntoResponse {
     let imei = payload.imei.clone();
 
-    let mut socket = match get_client_socket(&clients, &imei).await {
-        Ok(socket) => socket,
+    let connection = match get_client(&clients, &imei).await {
+        Ok(connection) => connection,
         Err(err) => {
             return (
-                StatusCode::NOT_FOUND,
+                err.status_code(),
                 Json(ChangeGearResponse {
                     success: false,
-                    message: err,
+                    message: err.to_string(),
                     imei,
                 }),
             );
@@ -62,27 +59,8 @@ pub async fn change_gear_handler(
     let throttle = Turn::DontSet;
     let taillight_flashing = Turn::DontSet;
 
-    let s7_command = commands::generate_s7_command(
-        &imei,
-        &headlight_switch,
-        &speed_mode,
-        &throttle,
-        &taillight_flashing,
-    );
-
-    if let Err(err) = send_command(&mut socket, &s7_command).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ChangeGearResponse {
-                success: false,
-                message: err,
-                imei,
-            }),
-        );
-    }
-
-    if let Err(err) = handle_s7_response(
-        &mut socket,
+    if let Err(err) = request_s7(
+        &connection,
         &imei,
         &headlight_switch,
         &speed_mode,
@@ -92,10 +70,10 @@ pub async fn change_gear_handler(
     .await
     {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            err.status_code(),
             Json(ChangeGearResponse {
                 success: false,
-                message: err,
+                message: err.to_string(),
                 imei,
             }),
         );
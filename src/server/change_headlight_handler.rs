@@ -1,6 +1,5 @@
 use crate::{
     server::command_enums::{SpeedMode, Turn},
-    server::commands,
     server::handler::*,
     server::ClientMap,
 };
@@ -30,15 +29,15 @@ pub async fn change_headlight_handler(
 ) -> impl IntoResponse {
     let imei = payload.imei.clone();
 
-    // Retrieve the client socket for the specified IMEI
-    let mut socket = match get_client_socket(&clients, &imei).await {
-        Ok(socket) => socket,
+    // Retrieve the connection for the specified IMEI
+    let connection = match get_client(&clients, &imei).await {
+        Ok(connection) => connection,
         Err(err) => {
             return (
-                StatusCode::NOT_FOUND,
+                err.status_code(),
                 Json(ChangeHeadlightResponse {
                     success: false,
-                    message: err,
+                    message: err.to_string(),
                     imei,
                 }),
             );
@@ -53,30 +52,9 @@ pub async fn change_headlight_handler(
     let throttle_response = Turn::DontSet;
     let taillights_flashing = Turn::DontSet;
 
-    // Generate the S7 command
-    let s7_command = commands::generate_s7_command(
-        &imei,
-        &headlight_switch,
-        &speed_mode,
-        &throttle_response,
-        &taillights_flashing,
-    );
-
-    // Send the command to the scooter
-    if let Err(err) = send_command(&mut socket, &s7_command).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ChangeHeadlightResponse {
-                success: false,
-                message: err,
-                imei,
-            }),
-        );
-    }
-
-    // Handle the response from the scooter
-    if let Err(err) = handle_s7_response(
-        &mut socket,
+    // Send the S7 command and wait for the scooter to echo it
+    if let Err(err) = request_s7(
+        &connection,
         &imei,
         &headlight_switch,
         &speed_mode,
@@ -86,10 +64,10 @@ pub async fn change_headlight_handler(
     .await
     {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            err.status_code(),
             Json(ChangeHeadlightResponse {
                 success: false,
-                message: err,
+                message: err.to_string(),
                 imei,
             }),
         );
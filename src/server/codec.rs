@@ -0,0 +1,125 @@
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const TERMINATOR: &[u8] = b"#\n";
+const FRAME_START: u8 = b'*';
+
+/// Splits a byte stream into `*SCOR ... #\n` frames.
+///
+/// Bytes are buffered until a `#\n` terminator arrives, so frames split across reads or
+/// coalesced into one read are both handled. Anything before the `*` that starts a frame,
+/// such as the `0xFFFF` reserved header, is discarded.
+#[derive(Debug)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    max_frame_size: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FrameError {
+    TooLarge { size: usize, max: usize },
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameError::TooLarge { size, max } => {
+                write!(
+                    f,
+                    "Frame of {} bytes exceeds maximum of {} bytes",
+                    size, max
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl FrameDecoder {
+    pub fn new(max_frame_size: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_frame_size,
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete frame, including its `#\n` terminator.
+    ///
+    /// When the buffered data grows past the maximum frame size without a terminator the
+    /// buffer is discarded and an error is returned.
+    pub fn next_frame(&mut self) -> Option<Result<String, FrameError>> {
+        match find_terminator(&self.buffer) {
+            Some(position) => {
+                let end = position + TERMINATOR.len();
+                let raw: Vec<u8> = self.buffer.drain(..end).collect();
+                let frame = strip_header(&raw);
+
+                if frame.len() > self.max_frame_size {
+                    return Some(Err(FrameError::TooLarge {
+                        size: frame.len(),
+                        max: self.max_frame_size,
+                    }));
+                }
+
+                Some(Ok(String::from_utf8_lossy(frame).to_string()))
+            }
+            None if self.buffer.len() > self.max_frame_size => {
+                let size = self.buffer.len();
+                self.buffer.clear();
+                Some(Err(FrameError::TooLarge {
+                    size,
+                    max: self.max_frame_size,
+                }))
+            }
+            None => None,
+        }
+    }
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new(crate::config::MAX_FRAME_SIZE)
+    }
+}
+
+fn find_terminator(buffer: &[u8]) -> Option<usize> {
+    buffer
+        .windows(TERMINATOR.len())
+        .position(|window| window == TERMINATOR)
+}
+
+fn strip_header(raw: &[u8]) -> &[u8] {
+    match raw.iter().position(|&byte| byte == FRAME_START) {
+        Some(start) => &raw[start..],
+        None => raw,
+    }
+}
+
+/// Reads from `reader` until the decoder yields a complete frame.
+///
+/// Returns `Ok(None)` once the peer closes the connection. Oversized frames are reported as
+/// `InvalidData` errors.
+pub async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    decoder: &mut FrameDecoder,
+) -> std::io::Result<Option<String>> {
+    let mut buffer = [0; 1024];
+
+    loop {
+        if let Some(frame) = decoder.next_frame() {
+            return frame
+                .map(Some)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+        }
+
+        let n = reader.read(&mut buffer).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        decoder.push(&buffer[..n]);
+    }
+}
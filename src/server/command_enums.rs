@@ -1,7 +1,15 @@
-#[derive(Debug)]
+use serde::Deserialize;
+
+use crate::commands::scooter_setting_command::{
+    HeadlightSwitch, ModeSetting, TaillightsFlashing, ThrottleResponse,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Turn {
     Off,
     On,
+    #[default]
     DontSet,
 }
 
@@ -15,11 +23,43 @@ impl From<&Turn> for u8 {
     }
 }
 
-#[derive(Debug)]
+impl From<&Turn> for HeadlightSwitch {
+    fn from(turn: &Turn) -> Self {
+        match turn {
+            Turn::DontSet => HeadlightSwitch::NoSet,
+            Turn::Off => HeadlightSwitch::Shutdown,
+            Turn::On => HeadlightSwitch::Open,
+        }
+    }
+}
+
+impl From<&Turn> for ThrottleResponse {
+    fn from(turn: &Turn) -> Self {
+        match turn {
+            Turn::DontSet => ThrottleResponse::NoSet,
+            Turn::Off => ThrottleResponse::Shutdown,
+            Turn::On => ThrottleResponse::Open,
+        }
+    }
+}
+
+impl From<&Turn> for TaillightsFlashing {
+    fn from(turn: &Turn) -> Self {
+        match turn {
+            Turn::DontSet => TaillightsFlashing::NoSet,
+            Turn::Off => TaillightsFlashing::Shutdown,
+            Turn::On => TaillightsFlashing::Open,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SpeedMode {
     Low,
     Medium,
     High,
+    #[default]
     DontSet,
 }
 impl From<&SpeedMode> for u8 {
@@ -32,6 +72,16 @@ impl From<&SpeedMode> for u8 {
         }
     }
 }
+impl From<&SpeedMode> for ModeSetting {
+    fn from(speed: &SpeedMode) -> Self {
+        match speed {
+            SpeedMode::DontSet => ModeSetting::NoSet,
+            SpeedMode::Low => ModeSetting::LowSpeed,
+            SpeedMode::Medium => ModeSetting::MediumSpeed,
+            SpeedMode::High => ModeSetting::HighSpeed,
+        }
+    }
+}
 impl TryFrom<u8> for SpeedMode {
     type Error = String;
 
@@ -1,64 +1,14 @@
-use serde::Deserialize;
-use std::convert::TryFrom;
-use tokio::io::AsyncWriteExt;
+use chrono::{DateTime, Utc};
+
+use crate::commands::beep_command::BeepPlayContent;
+use crate::commands::scooter_command::ScooterCommand;
+use crate::commands::unlock_command::R0Operation;
 
 use super::{
     command_enums::{SpeedMode, Turn},
     ClientMap,
 };
 
-#[derive(Debug, Deserialize)]
-pub enum R0Operation {
-    Unlock,
-    Lock,
-    RFIDCardUnlock,
-    RFIDCardLock,
-}
-
-impl TryFrom<&R0Operation> for &'static str {
-    type Error = ();
-
-    fn try_from(operation: &R0Operation) -> Result<Self, Self::Error> {
-        match operation {
-            R0Operation::Unlock => Ok("0"),
-            R0Operation::Lock => Ok("1"),
-            R0Operation::RFIDCardUnlock => Ok("2"),
-            R0Operation::RFIDCardLock => Ok("3"),
-        }
-    }
-}
-
-impl<'a> TryFrom<&'a str> for R0Operation {
-    type Error = &'a str;
-
-    fn try_from(operation_string: &'a str) -> Result<Self, Self::Error> {
-        match operation_string {
-            "0" => Ok(R0Operation::Unlock),
-            "1" => Ok(R0Operation::Lock),
-            "2" => Ok(R0Operation::RFIDCardUnlock),
-            "3" => Ok(R0Operation::RFIDCardLock),
-            _ => Err(operation_string),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub enum Status {
-    Success,
-    Failure,
-    KeyError,
-}
-impl From<&str> for Status {
-    fn from(status_string: &str) -> Self {
-        match status_string {
-            "0" => Status::Success,
-            "1" => Status::Failure,
-            "2" => Status::KeyError,
-            _ => panic!("Invalid status code: {}", status_string),
-        }
-    }
-}
-
 pub fn generate_r0_command(
     imei: &str,
     operation: &R0Operation,
@@ -66,37 +16,59 @@ pub fn generate_r0_command(
     user_id: u32,
     timestamp: i64,
 ) -> String {
-    let operation: &str = operation.try_into().map_err(|_| ()).unwrap();
-    generate_command(
-        imei,
-        "R0",
-        &[
-            &operation,
-            &key_duration.to_string(),
-            &user_id.to_string(),
-            &timestamp.to_string(),
-        ],
-    )
+    generate(ScooterCommand::UnlockOrLockRequest {
+        imei: imei.to_string(),
+        operation: operation.clone(),
+        key_duration,
+        user_id,
+        timestamp: unix_time(timestamp),
+    })
 }
 
 pub fn generate_l0_command(imei: &str, key: &str, user_id: u32, timestamp: i64) -> String {
-    generate_command(
-        imei,
-        "L0",
-        &[key, &user_id.to_string(), &timestamp.to_string()],
-    )
+    generate(ScooterCommand::UnlockRequest {
+        imei: imei.to_string(),
+        key: key.to_string(),
+        user_id,
+        timestamp: unix_time(timestamp),
+    })
 }
 
 pub fn generate_l0_ack(imei: &str) -> String {
-    generate_command(imei, "L0", &[])
+    generate_acknowledgement(imei, "L0")
 }
 
 pub fn generate_l1_command(imei: &str, key: &str) -> String {
-    generate_command(imei, "L1", &[key])
+    generate(ScooterCommand::LockRequest {
+        imei: imei.to_string(),
+        key: key.to_string(),
+    })
 }
 
-pub fn generate_l1_ack(imei: &str) -> String {
-    generate_command(imei, "L1", &[])
+pub fn generate_d0_command(imei: &str) -> String {
+    generate(ScooterCommand::PositionRequest {
+        imei: imei.to_string(),
+    })
+}
+
+pub fn generate_s6_command(imei: &str) -> String {
+    generate(ScooterCommand::StatusRequest {
+        imei: imei.to_string(),
+    })
+}
+
+pub fn generate_d1_command(imei: &str, interval: u16) -> String {
+    generate(ScooterCommand::TrackingIntervalRequest {
+        imei: imei.to_string(),
+        interval,
+    })
+}
+
+pub fn generate_v0_command(imei: &str, play_content: &BeepPlayContent) -> String {
+    generate(ScooterCommand::BeepRequest {
+        imei: imei.to_string(),
+        play_content: play_content.clone(),
+    })
 }
 
 pub fn generate_s7_command(
@@ -106,37 +78,59 @@ pub fn generate_s7_command(
     throttle: &Turn,
     taillights_flashing: &Turn,
 ) -> String {
-    generate_command(
-        imei,
-        "S7",
-        &[
-            &Into::<u8>::into(headlight).to_string(),
-            &Into::<u8>::into(speed_mode).to_string(),
-            &Into::<u8>::into(throttle).to_string(),
-            &Into::<u8>::into(taillights_flashing).to_string(),
-        ],
-    )
-}
-
-pub fn generate_command(imei: &str, command: &str, content: &[&str]) -> String {
-    let reserved_header = format!("{:#06X}", 0xFFFF);
-    let vendor = crate::config::VENDOR;
-
-    let mut command = format!(
-        "{reserved_header}*SCOS,{vendor},{imei},{command}",
-        reserved_header = reserved_header,
-        vendor = vendor,
-        imei = imei,
-        command = command
-    );
-    if !content.is_empty() {
-        command.push_str(",");
-        command.push_str(&content.join(","));
+    generate(ScooterCommand::SettingRequest {
+        imei: imei.to_string(),
+        headlight_switch: headlight.into(),
+        mode_setting: speed_mode.into(),
+        throttle_response: throttle.into(),
+        taillights_flashing: taillights_flashing.into(),
+    })
+}
+
+pub fn generate_l1_ack(imei: &str) -> String {
+    generate_acknowledgement(imei, "L1")
+}
+
+pub fn generate_q0_ack(imei: &str) -> String {
+    generate_acknowledgement(imei, "Q0")
+}
+
+pub fn generate_h0_ack(imei: &str) -> String {
+    generate_acknowledgement(imei, "H0")
+}
+
+pub fn generate_w0_ack(imei: &str) -> String {
+    generate_acknowledgement(imei, "W0")
+}
+
+/// Returns the acknowledgement the protocol expects for an inbound `command` frame, if any.
+pub fn generate_ack(imei: &str, command: &str) -> Option<String> {
+    match command {
+        "Q0" => Some(generate_q0_ack(imei)),
+        "H0" => Some(generate_h0_ack(imei)),
+        "W0" => Some(generate_w0_ack(imei)),
+        "L0" => Some(generate_l0_ack(imei)),
+        "L1" => Some(generate_l1_ack(imei)),
+        _ => None,
     }
-    command.push('#');
-    command.push('\n');
+}
+
+fn generate_acknowledgement(imei: &str, command: &str) -> String {
+    generate(ScooterCommand::Acknowledgement {
+        imei: imei.to_string(),
+        command: command.to_string(),
+    })
+}
+
+/// `seconds` since the Unix epoch. Timestamps sent to scooters come from the clock, so they
+/// are always in range.
+fn unix_time(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(seconds, 0).expect("timestamp is in range")
+}
 
-    command
+/// Encodes `command` and prepends the reserved header.
+pub fn generate(command: ScooterCommand) -> String {
+    format!("{:#06X}{}", 0xFFFF, command.encode())
 }
 
 pub async fn send_command_to_imei(
@@ -144,11 +138,12 @@ pub async fn send_command_to_imei(
     imei: &str,
     command: &str,
 ) -> std::io::Result<()> {
-    let clients_lock = clients.lock().await;
+    let client = clients.lock().await.get(imei).cloned();
 
-    if let Some(client) = clients_lock.get(imei) {
-        let mut client_lock = client.lock().await; // Lock the TcpStream
-        client_lock.write_all(command.as_bytes()).await?;
+    if let Some(client) = client {
+        super::handler::send_command(&client, command)
+            .await
+            .map_err(std::io::Error::other)?;
         println!("Command sent to {}: {}", imei, command);
     } else {
         println!("No client found with IMEI: {}", imei);
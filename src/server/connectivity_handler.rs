@@ -0,0 +1,48 @@
+use crate::server::presence::{Connectivity, PresenceMap};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct ConnectivityResponse {
+    pub success: bool,
+    pub message: String,
+    pub imei: String,
+    pub connectivity: Option<Connectivity>,
+    pub connected: Option<bool>,
+    pub last_seen: Option<String>, // RFC 3339
+}
+
+pub async fn connectivity_handler(
+    State(presence): State<PresenceMap>,
+    Path(imei): Path<String>,
+) -> impl IntoResponse {
+    let Some(entry) = presence.lock().unwrap().get(&imei).cloned() else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ConnectivityResponse {
+                success: false,
+                message: format!("Scooter with IMEI {} has never connected", imei),
+                imei,
+                connectivity: None,
+                connected: None,
+                last_seen: None,
+            }),
+        );
+    };
+
+    (
+        StatusCode::OK,
+        Json(ConnectivityResponse {
+            success: true,
+            message: format!("Scooter is {:?}", entry.connectivity),
+            imei,
+            connectivity: Some(entry.connectivity),
+            connected: Some(entry.connected),
+            last_seen: Some(entry.last_seen.to_rfc3339()),
+        }),
+    )
+}
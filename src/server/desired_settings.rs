@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    commands::{
+        parser::parse_command,
+        scooter_command::ScooterCommand,
+        scooter_setting_command::{
+            HeadlightSwitch, ModeSetting, TaillightsFlashing, ThrottleResponse,
+        },
+    },
+    errors::AppError,
+};
+
+use super::{
+    commands,
+    events::{self, DeviceEvent},
+    handler::{send_request, Connection},
+    registry::{self, ScooterSettings},
+    AppState,
+};
+
+/// Per-scooter desired S7 settings set through the API, taking precedence over the fleet policy.
+pub type SettingsOverrides = Arc<Mutex<HashMap<String, ScooterSettings>>>;
+
+/// The fleet-wide desired settings from the `DESIRED_*` environment variables.
+pub fn from_env() -> ScooterSettings {
+    let setting = |name: &str| crate::config::desired_setting(name);
+    ScooterSettings::reported(
+        &setting("HEADLIGHT_SWITCH")
+            .and_then(|value| HeadlightSwitch::try_from(value).ok())
+            .unwrap_or(HeadlightSwitch::NoSet),
+        &setting("MODE_SETTING")
+            .and_then(|value| ModeSetting::try_from(value).ok())
+            .unwrap_or(ModeSetting::NoSet),
+        &setting("THROTTLE_RESPONSE")
+            .and_then(|value| ThrottleResponse::try_from(value).ok())
+            .unwrap_or(ThrottleResponse::NoSet),
+        &setting("TAILLIGHTS_FLASHING")
+            .and_then(|value| TaillightsFlashing::try_from(value).ok())
+            .unwrap_or(TaillightsFlashing::NoSet),
+    )
+}
+
+/// The settings `imei` should have: its overrides on top of the fleet policy.
+pub fn desired(state: &AppState, imei: &str) -> ScooterSettings {
+    let mut settings = state.settings_policy.clone();
+    if let Some(overrides) = state.desired_settings.lock().unwrap().get(imei) {
+        settings.merge(overrides.clone());
+    }
+    settings
+}
+
+/// The S7 request that sets `settings`, leaving the unset ones alone.
+pub fn command(imei: &str, settings: &ScooterSettings) -> ScooterCommand {
+    ScooterCommand::SettingRequest {
+        imei: imei.to_string(),
+        headlight_switch: settings
+            .headlight_switch
+            .clone()
+            .unwrap_or(HeadlightSwitch::NoSet),
+        mode_setting: settings.mode_setting.clone().unwrap_or(ModeSetting::NoSet),
+        throttle_response: settings
+            .throttle_response
+            .clone()
+            .unwrap_or(ThrottleResponse::NoSet),
+        taillights_flashing: settings
+            .taillights_flashing
+            .clone()
+            .unwrap_or(TaillightsFlashing::NoSet),
+    }
+}
+
+/// Re-sends the desired settings and waits for the S7 echo on its own task, since the echo is
+/// delivered by the connection's reader that calls this.
+///
+/// An echo that disagrees is reported by [`check_drift`] as it passes through the reader. When
+/// no echo arrives in time, every desired setting is reported as drifted.
+pub fn apply(state: &AppState, imei: &str, connection: Arc<Connection>) {
+    let settings = desired(state, imei);
+    if settings.is_empty() {
+        return;
+    }
+
+    let command = commands::generate(command(imei, &settings));
+    let state = state.clone();
+    let imei = imei.to_string();
+    tokio::spawn(async move {
+        let expected_imei = imei.clone();
+        let echo = send_request(
+            &connection,
+            &command,
+            "S7",
+            crate::config::command_timeout("S7"),
+            move |frame| {
+                matches!(
+                    parse_command(frame),
+                    Ok(ScooterCommand::ScooterSetting { ref imei, .. }) if *imei == expected_imei
+                )
+            },
+        )
+        .await;
+
+        match echo {
+            Ok(_) => println!("Re-applied desired settings of {}", imei),
+            Err(AppError::DeviceTimeout(err)) => {
+                println!("Settings of {} were not confirmed: {}", imei, err);
+                let reported = registry::get(&state.registry, &imei)
+                    .map(|device| device.settings)
+                    .unwrap_or_default();
+                events::publish(
+                    &state.events,
+                    DeviceEvent::SettingsDrift {
+                        imei,
+                        desired: settings,
+                        reported,
+                    },
+                );
+            }
+            Err(err) => println!("Failed to re-apply settings of {}: {}", imei, err),
+        }
+    });
+}
+
+/// The desired settings that `reported` contradicts. Settings the frame does not carry are not
+/// counted as drift.
+pub fn drift(desired: &ScooterSettings, reported: &ScooterSettings) -> ScooterSettings {
+    fn differs<T: Clone + PartialEq>(desired: &Option<T>, reported: &Option<T>) -> Option<T> {
+        match (desired, reported) {
+            (Some(desired), Some(reported)) if desired != reported => Some(desired.clone()),
+            _ => None,
+        }
+    }
+
+    ScooterSettings {
+        headlight_switch: differs(&desired.headlight_switch, &reported.headlight_switch),
+        mode_setting: differs(&desired.mode_setting, &reported.mode_setting),
+        throttle_response: differs(&desired.throttle_response, &reported.throttle_response),
+        taillights_flashing: differs(&desired.taillights_flashing, &reported.taillights_flashing),
+    }
+}
+
+/// Publishes a `SettingsDrift` event when an S7 echo disagrees with the desired settings.
+pub fn check_drift(state: &AppState, imei: &str, command: &ScooterCommand) {
+    let ScooterCommand::ScooterSetting {
+        headlight_switch,
+        mode_setting,
+        throttle_response,
+        taillights_flashing,
+        ..
+    } = command
+    else {
+        return;
+    };
+
+    let reported = ScooterSettings::reported(
+        headlight_switch,
+        mode_setting,
+        throttle_response,
+        taillights_flashing,
+    );
+    let desired = drift(&desired(state, imei), &reported);
+    if desired.is_empty() {
+        return;
+    }
+
+    println!(
+        "Settings of {} drifted: desired {:?}, reported {:?}",
+        imei, desired, reported
+    );
+    events::publish(
+        &state.events,
+        DeviceEvent::SettingsDrift {
+            imei: imei.to_string(),
+            desired,
+            reported,
+        },
+    );
+}
@@ -0,0 +1,153 @@
+use crate::server::{
+    desired_settings,
+    handler::*,
+    presence,
+    registry::{self, ScooterSettings},
+    settings_handler::SettingsRequest,
+    AppState,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct DesiredSettingsResponse {
+    pub success: bool,
+    pub message: String,
+    pub imei: String,
+    /// The settings kept on the scooter, including those from the fleet policy.
+    pub desired: ScooterSettings,
+    /// The scooter's settings as last confirmed.
+    pub settings: Option<ScooterSettings>,
+}
+
+/// Stores desired settings for the scooter and applies them right away if it is connected.
+/// Omitted fields keep their current desired value.
+pub async fn desired_settings_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+    Json(payload): Json<SettingsRequest>,
+) -> impl IntoResponse {
+    // Desired settings are only kept for scooters that exist
+    if !presence::is_known(&state.presence, &imei) {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(DesiredSettingsResponse {
+                success: false,
+                message: format!("Scooter with IMEI {} has never connected", imei),
+                desired: desired_settings::desired(&state, &imei),
+                imei,
+                settings: None,
+            }),
+        );
+    }
+
+    let requested = ScooterSettings::reported(
+        &(&payload.headlight_switch).into(),
+        &(&payload.speed_mode).into(),
+        &(&payload.throttle_response).into(),
+        &(&payload.taillights_flashing).into(),
+    );
+    if requested.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(DesiredSettingsResponse {
+                success: false,
+                message: "No settings to change".to_string(),
+                desired: desired_settings::desired(&state, &imei),
+                imei,
+                settings: None,
+            }),
+        );
+    }
+
+    state
+        .desired_settings
+        .lock()
+        .unwrap()
+        .entry(imei.clone())
+        .or_default()
+        .merge(requested);
+    let desired = desired_settings::desired(&state, &imei);
+
+    let connection = match get_client(&state.clients, &imei).await {
+        Ok(connection) => connection,
+        Err(_) => {
+            return (
+                StatusCode::ACCEPTED,
+                Json(DesiredSettingsResponse {
+                    success: true,
+                    message: format!(
+                        "Scooter with IMEI {} is offline; the settings apply when it reconnects",
+                        imei
+                    ),
+                    imei,
+                    desired,
+                    settings: None,
+                }),
+            );
+        }
+    };
+
+    if let Err(err) = request_settings(&connection, &imei, &desired).await {
+        return (
+            err.status_code(),
+            Json(DesiredSettingsResponse {
+                success: false,
+                message: err.to_string(),
+                imei,
+                desired,
+                settings: None,
+            }),
+        );
+    }
+
+    let settings = registry::get(&state.registry, &imei).map(|device| device.settings);
+    (
+        StatusCode::OK,
+        Json(DesiredSettingsResponse {
+            success: true,
+            message: "Desired settings applied".to_string(),
+            imei,
+            desired,
+            settings,
+        }),
+    )
+}
+
+/// Removes the scooter's desired settings so that only the fleet policy is kept. The scooter
+/// is not sent anything; its settings return to the defaults on the next restart or unlock.
+pub async fn clear_desired_settings_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+) -> impl IntoResponse {
+    let removed = state.desired_settings.lock().unwrap().remove(&imei);
+    let desired = desired_settings::desired(&state, &imei);
+    let settings = registry::get(&state.registry, &imei).map(|device| device.settings);
+
+    match removed {
+        Some(_) => (
+            StatusCode::OK,
+            Json(DesiredSettingsResponse {
+                success: true,
+                message: "Desired settings cleared".to_string(),
+                imei,
+                desired,
+                settings,
+            }),
+        ),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(DesiredSettingsResponse {
+                success: false,
+                message: format!("Scooter with IMEI {} has no desired settings", imei),
+                imei,
+                desired,
+                settings,
+            }),
+        ),
+    }
+}
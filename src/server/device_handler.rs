@@ -0,0 +1,132 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+};
+
+use crate::server::{
+    presence::{Connectivity, Presence},
+    registry::{self, DeviceState},
+    AppState,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A scooter's last-known state together with its connection metadata.
+#[derive(Debug, Serialize)]
+pub struct DeviceSummary {
+    pub imei: String,
+    pub connectivity: Option<Connectivity>,
+    /// Whether the scooter currently has an open connection.
+    pub connected: bool,
+    pub peer_address: Option<String>,
+    pub connected_since: Option<DateTime<Utc>>, // RFC 3339
+    pub last_frame_at: Option<DateTime<Utc>>,   // RFC 3339
+    pub state: Option<DeviceState>,
+}
+
+#[derive(Serialize)]
+pub struct DeviceResponse {
+    pub success: bool,
+    pub message: String,
+    pub device: Option<DeviceSummary>,
+}
+
+pub async fn device_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+) -> impl IntoResponse {
+    match device_summary(&state, &imei).await {
+        Some(device) => (
+            StatusCode::OK,
+            Json(DeviceResponse {
+                success: true,
+                message: "Device found".to_string(),
+                device: Some(device),
+            }),
+        ),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(DeviceResponse {
+                success: false,
+                message: format!("Scooter with IMEI {} has never connected", imei),
+                device: None,
+            }),
+        ),
+    }
+}
+
+/// Summaries of every scooter seen since startup, ordered by IMEI.
+pub async fn device_summaries(state: &AppState) -> Vec<DeviceSummary> {
+    let connections: HashMap<String, _> = state
+        .clients
+        .lock()
+        .await
+        .iter()
+        .map(|(imei, connection)| {
+            (
+                imei.clone(),
+                (connection.peer_address, connection.connected_since),
+            )
+        })
+        .collect();
+    let presence = state.presence.lock().unwrap().clone();
+    let registry = state.registry.lock().unwrap().clone();
+
+    let imeis: BTreeSet<&String> = connections
+        .keys()
+        .chain(presence.keys())
+        .chain(registry.keys())
+        .collect();
+
+    imeis
+        .into_iter()
+        .map(|imei| {
+            summarize(
+                imei,
+                connections.get(imei).copied(),
+                presence.get(imei),
+                registry.get(imei).cloned(),
+            )
+        })
+        .collect()
+}
+
+/// Summary of one scooter, or `None` if it has not been seen since startup.
+pub async fn device_summary(state: &AppState, imei: &str) -> Option<DeviceSummary> {
+    let connection = state
+        .clients
+        .lock()
+        .await
+        .get(imei)
+        .map(|connection| (connection.peer_address, connection.connected_since));
+    let presence = state.presence.lock().unwrap().get(imei).cloned();
+    let device_state = registry::get(&state.registry, imei);
+
+    if connection.is_none() && presence.is_none() && device_state.is_none() {
+        return None;
+    }
+    Some(summarize(imei, connection, presence.as_ref(), device_state))
+}
+
+fn summarize(
+    imei: &str,
+    connection: Option<(Option<SocketAddr>, DateTime<Utc>)>,
+    presence: Option<&Presence>,
+    state: Option<DeviceState>,
+) -> DeviceSummary {
+    DeviceSummary {
+        imei: imei.to_string(),
+        connectivity: presence.map(|presence| presence.connectivity),
+        connected: connection.is_some(),
+        peer_address: connection
+            .and_then(|(address, _)| address.map(|address| address.to_string())),
+        connected_since: connection.map(|(_, since)| since),
+        last_frame_at: presence.map(|presence| presence.last_seen),
+        state,
+    }
+}
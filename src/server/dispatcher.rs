@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use tokio::sync::oneshot;
+
+type Matcher = Box<dyn Fn(&str) -> bool + Send>;
+
+struct PendingRequest {
+    id: u64,
+    matches: Matcher,
+    reply: oneshot::Sender<String>,
+}
+
+/// Outstanding requests sent to one scooter, keyed by the command code of the expected reply.
+///
+/// The connection's reader task offers every frame to the table first. A frame is routed to
+/// the oldest waiting request for its command code whose matcher accepts it; frames nobody
+/// claims are handed back so they can go to the normal event pipeline.
+#[derive(Default)]
+pub struct PendingTable {
+    next_id: u64,
+    requests: HashMap<String, Vec<PendingRequest>>,
+}
+
+impl PendingTable {
+    /// Registers interest in the next `code` reply accepted by `matches`.
+    ///
+    /// Returns an id that can be passed to [`PendingTable::cancel`] and the receiver the reply
+    /// is delivered on.
+    pub fn register(
+        &mut self,
+        code: &str,
+        matches: impl Fn(&str) -> bool + Send + 'static,
+    ) -> (u64, oneshot::Receiver<String>) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let (reply, receiver) = oneshot::channel();
+        self.requests
+            .entry(code.to_string())
+            .or_default()
+            .push(PendingRequest {
+                id,
+                matches: Box::new(matches),
+                reply,
+            });
+
+        (id, receiver)
+    }
+
+    pub fn cancel(&mut self, code: &str, id: u64) {
+        if let Some(requests) = self.requests.get_mut(code) {
+            requests.retain(|request| request.id != id);
+            if requests.is_empty() {
+                self.requests.remove(code);
+            }
+        }
+    }
+
+    /// Delivers `frame` to a waiting request. Returns the frame back if nobody claimed it.
+    pub fn resolve(&mut self, frame: String) -> Option<String> {
+        let Some(code) = command_code(&frame) else {
+            return Some(frame);
+        };
+        let Some(requests) = self.requests.get_mut(code) else {
+            return Some(frame);
+        };
+
+        // Callers that gave up no longer need their slot.
+        requests.retain(|request| !request.reply.is_closed());
+
+        let claimed = requests
+            .iter()
+            .position(|request| (request.matches)(&frame))
+            .map(|index| requests.remove(index));
+        if requests.is_empty() {
+            let code = code.to_string();
+            self.requests.remove(&code);
+        }
+
+        match claimed {
+            Some(request) => request.reply.send(frame).err(),
+            None => Some(frame),
+        }
+    }
+
+    /// Drops every waiting request; their callers see the connection as closed.
+    pub fn clear(&mut self) {
+        self.requests.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Extracts the command code, e.g. `R0`, from a `*SCOR,<vendor>,<imei>,<code>,...` frame.
+pub fn command_code(frame: &str) -> Option<&str> {
+    frame
+        .trim_end_matches("#\n")
+        .split(',')
+        .nth(3)
+        .filter(|code| !code.is_empty())
+}
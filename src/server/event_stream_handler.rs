@@ -0,0 +1,101 @@
+use std::convert::Infallible;
+
+use crate::server::{
+    events::{DeviceEvent, EventSender},
+    AppState,
+};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+
+/// Comma-separated filters, e.g. `?imei=123456789123456&events=frame,alarm`. An omitted
+/// filter lets everything through.
+#[derive(Debug, Default, Deserialize)]
+pub struct EventFilter {
+    pub imei: Option<String>,
+    /// `event` tags as serialized, e.g. `frame`, `disconnected` or `alarm`.
+    pub events: Option<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &DeviceEvent) -> bool {
+        fn listed(list: &Option<String>, value: &str) -> bool {
+            list.as_deref()
+                .is_none_or(|list| list.split(',').any(|item| item.trim() == value))
+        }
+
+        listed(&self.imei, event.imei()) && listed(&self.events, event.kind())
+    }
+}
+
+/// `GET /events` as Server-Sent Events, one `data:` JSON object per device event, named after
+/// its `event` tag.
+pub async fn sse_handler(
+    State(state): State<AppState>,
+    Query(filter): Query<EventFilter>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(state.events.subscribe()).filter_map(move |event| {
+        let event = match event {
+            Ok(event) if filter.matches(&event) => {
+                Event::default().event(event.kind()).json_data(&event).ok()
+            }
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                Some(Event::default().event("lagged").data(skipped.to_string()))
+            }
+        };
+        std::future::ready(event.map(Ok))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// `GET /events/ws` upgrades to a WebSocket that receives each device event as a JSON text
+/// message. Messages from the client are ignored.
+pub async fn ws_handler(
+    State(state): State<AppState>,
+    Query(filter): Query<EventFilter>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    upgrade.on_upgrade(move |socket| forward_events(socket, state.events, filter))
+}
+
+async fn forward_events(mut socket: WebSocket, events: EventSender, filter: EventFilter) {
+    let mut events = events.subscribe();
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        let text = match event {
+            Ok(event) if filter.matches(&event) => match serde_json::to_string(&event) {
+                Ok(text) => text,
+                Err(_) => continue,
+            },
+            Ok(_) => continue,
+            Err(RecvError::Lagged(skipped)) => {
+                format!(r#"{{"event":"lagged","skipped":{}}}"#, skipped)
+            }
+            Err(RecvError::Closed) => return,
+        };
+        if socket.send(Message::Text(text)).await.is_err() {
+            return;
+        }
+    }
+}
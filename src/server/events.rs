@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::commands::scooter_command::ScooterCommand;
+
+use super::{alarms::Alarm, presence::Connectivity, registry::ScooterSettings};
+
+/// Serialized with an `event` tag, e.g. `{"event":"frame","imei":"...","command":{...}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DeviceEvent {
+    Frame {
+        imei: String,
+        command: ScooterCommand,
+    },
+    Disconnected {
+        imei: String,
+        reason: DisconnectReason,
+    },
+    ConnectivityChanged {
+        imei: String,
+        from: Connectivity,
+        to: Connectivity,
+    },
+    /// The scooter confirmed an L0 unlock, whether or not a request was waiting for it.
+    Unlocked { imei: String },
+    /// The scooter confirmed an L1 lock.
+    Locked {
+        imei: String,
+        /// Minutes ridden since the scooter was unlocked.
+        cycling_time: u32,
+    },
+    /// A W0 alarm that was not a repeat within the deduplication window.
+    Alarm { imei: String, alarm: Alarm },
+    /// An S7 echo disagreed with the desired settings. `desired` holds only the drifted fields.
+    /// When a re-applied S7 was never echoed, `desired` holds all of them and `reported` the
+    /// settings last confirmed.
+    SettingsDrift {
+        imei: String,
+        desired: ScooterSettings,
+        reported: ScooterSettings,
+    },
+}
+
+impl DeviceEvent {
+    pub fn imei(&self) -> &str {
+        match self {
+            DeviceEvent::Frame { imei, .. }
+            | DeviceEvent::Disconnected { imei, .. }
+            | DeviceEvent::ConnectivityChanged { imei, .. }
+            | DeviceEvent::Unlocked { imei }
+            | DeviceEvent::Locked { imei, .. }
+            | DeviceEvent::Alarm { imei, .. }
+            | DeviceEvent::SettingsDrift { imei, .. } => imei,
+        }
+    }
+
+    /// The `event` tag the event is serialized with.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DeviceEvent::Frame { .. } => "frame",
+            DeviceEvent::Disconnected { .. } => "disconnected",
+            DeviceEvent::ConnectivityChanged { .. } => "connectivity_changed",
+            DeviceEvent::Unlocked { .. } => "unlocked",
+            DeviceEvent::Locked { .. } => "locked",
+            DeviceEvent::Alarm { .. } => "alarm",
+            DeviceEvent::SettingsDrift { .. } => "settings_drift",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisconnectReason {
+    /// The scooter closed the socket.
+    Closed,
+    ReadError(String),
+    /// Nothing was received within the idle timeout.
+    IdleTimeout,
+    /// The scooter signed in again on a new socket. No `Disconnected` event is published for it.
+    Replaced,
+    /// A write to the scooter did not complete within the write timeout.
+    WriteTimeout,
+}
+
+pub type EventSender = broadcast::Sender<DeviceEvent>;
+
+pub fn channel() -> EventSender {
+    let (sender, _) = broadcast::channel(crate::config::EVENT_CHANNEL_CAPACITY);
+    sender
+}
+
+pub fn publish(events: &EventSender, event: DeviceEvent) {
+    // Sending only fails when nobody is subscribed, which is not an error for the reader.
+    let _ = events.send(event);
+}
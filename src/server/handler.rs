@@ -1,43 +1,302 @@
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
-use crate::server::commands::R0Operation;
+use chrono::{DateTime, Utc};
+
+use crate::commands::unlock_command::R0Operation;
+use crate::commands::{
+    beep_command::BeepPlayContent,
+    parser::parse_command,
+    positioning_command::{PositioningIdentifier, PositioningResponse, Status},
+    scooter_command::ScooterCommand,
+    status_command::StatusReport,
+};
+use crate::errors::{AppError, ParseError};
+use crate::server::commands;
 use crate::server::protocol;
-use crate::{commands::parser::parse_command, config::USER_ID};
+use crate::{config, config::USER_ID};
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpStream,
-    sync::Mutex,
+    io::AsyncWriteExt,
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream,
+    },
+    sync::{Mutex, Notify},
+    time::timeout,
 };
 
+use super::alarms;
+use super::codec::{read_frame, FrameDecoder};
 use super::command_enums::{SpeedMode, Turn};
-use super::ClientMap;
+use super::desired_settings;
+use super::dispatcher::{command_code, PendingTable};
+use super::events::{self, DeviceEvent, DisconnectReason};
+use super::history::{CommandOutcome, CommandRecord, History};
+use super::presence;
+use super::registry::{self, ScooterSettings};
+use super::tracking;
+use super::{AppState, ClientMap};
 
-pub async fn handle_connection(mut socket: TcpStream, clients: ClientMap) -> std::io::Result<()> {
-    let mut buffer = vec![0; 1024];
+/// A registered scooter connection. The read half is owned by the connection's reader task,
+/// which routes replies to the requests waiting in `pending`.
+pub struct Connection {
+    writer: Arc<Mutex<OwnedWriteHalf>>,
+    pending: std::sync::Mutex<PendingTable>,
+    replaced: Notify,
+    /// Signalled when a write timed out, so the reader tears the connection down.
+    stalled: Notify,
+    write_timeout: Duration,
+    imei: String,
+    /// Where requests sent on this connection are audited.
+    history: Option<History>,
+    pub peer_address: Option<SocketAddr>,
+    pub connected_since: DateTime<Utc>,
+}
+
+impl Connection {
+    /// Number of requests still waiting for a reply from the scooter.
+    pub fn pending_requests(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+}
+
+pub async fn handle_connection(socket: TcpStream, state: AppState) -> std::io::Result<()> {
+    let peer_address = socket.peer_addr().ok();
+    let (mut reader, writer) = socket.into_split();
+    let mut decoder = FrameDecoder::default();
 
     // Read the initial message to register the client
-    let n = socket.read(&mut buffer).await?;
-    let initial_message = String::from_utf8_lossy(&buffer[..n]);
+    let Some(initial_message) = read_frame(&mut reader, &mut decoder).await? else {
+        return Ok(());
+    };
     println!("Received: {}", initial_message);
 
     // Extract IMEI from the initial message
-    if let Some(imei) = extract_imei(&initial_message) {
-        // Add to the global client map
-        let mut clients_lock = clients.lock().await;
-        clients_lock.insert(imei.clone(), Arc::new(Mutex::new(socket)));
-        println!("Client registered: {}", imei);
-
-        let parsed_message = parse_command(&initial_message);
-        println!("Parsed message: {:?}", parsed_message);
-    } else {
+    let Some(imei) = extract_imei(&initial_message) else {
         println!("Invalid initial message: {}", initial_message);
         return Ok(()); // Ignore the client if the message is invalid
+    };
+
+    let connection = Arc::new(Connection {
+        writer: Arc::new(Mutex::new(writer)),
+        pending: std::sync::Mutex::new(PendingTable::default()),
+        replaced: Notify::new(),
+        stalled: Notify::new(),
+        write_timeout: state.write_timeout,
+        imei: imei.clone(),
+        history: state.history.clone(),
+        peer_address,
+        connected_since: Utc::now(),
+    });
+
+    // Add to the global client map, replacing any stale connection for the same scooter
+    let stale = state
+        .clients
+        .lock()
+        .await
+        .insert(imei.clone(), connection.clone());
+    if let Some(stale) = stale {
+        println!(
+            "Client {} signed in again, closing previous connection",
+            imei
+        );
+        stale.replaced.notify_one();
     }
+    println!("Client registered: {}", imei);
 
-    // Continue handling client-specific logic...
+    dispatch_frame(&imei, initial_message, &connection, &state).await;
+
+    let reason = read_loop(reader, decoder, &imei, &connection, &state).await;
+    disconnect(&imei, &connection, &state, reason).await;
     Ok(())
 }
 
+/// Keeps decoding frames from the scooter until the connection ends, and returns why it ended.
+async fn read_loop(
+    mut reader: OwnedReadHalf,
+    mut decoder: FrameDecoder,
+    imei: &str,
+    connection: &Arc<Connection>,
+    state: &AppState,
+) -> DisconnectReason {
+    loop {
+        let frame = tokio::select! {
+            frame = timeout(state.idle_timeout, read_frame(&mut reader, &mut decoder)) => frame,
+            _ = connection.replaced.notified() => return DisconnectReason::Replaced,
+            _ = connection.stalled.notified() => return DisconnectReason::WriteTimeout,
+        };
+
+        let message = match frame {
+            Ok(Ok(Some(message))) => message,
+            Ok(Ok(None)) => return DisconnectReason::Closed,
+            Ok(Err(err)) => return DisconnectReason::ReadError(err.to_string()),
+            Err(_) => return DisconnectReason::IdleTimeout,
+        };
+
+        println!("Received from {}: {}", imei, message);
+
+        dispatch_frame(imei, message, connection, state).await;
+    }
+}
+
+/// Evicts the connection from the client map and fails any requests still waiting on it.
+async fn disconnect(
+    imei: &str,
+    connection: &Arc<Connection>,
+    state: &AppState,
+    reason: DisconnectReason,
+) {
+    println!("Client {} disconnected: {:?}", imei, reason);
+
+    let evicted = {
+        let mut clients = state.clients.lock().await;
+        // A re-sign-in may already have replaced this entry with a newer connection.
+        let current = clients
+            .get(imei)
+            .is_some_and(|current| Arc::ptr_eq(current, connection));
+        if current {
+            clients.remove(imei);
+        }
+        current
+    };
+
+    connection.pending.lock().unwrap().clear();
+    let _ = connection.writer.lock().await.shutdown().await;
+
+    // The scooter is still online on its new socket
+    if reason != DisconnectReason::Replaced {
+        events::publish(
+            &state.events,
+            DeviceEvent::Disconnected {
+                imei: imei.to_string(),
+                reason,
+            },
+        );
+    }
+
+    if evicted {
+        if let Some(transition) = presence::record_disconnected(&state.presence, imei) {
+            presence::publish_transition(state, imei, transition);
+        }
+    }
+}
+
+/// Drops frames carrying another scooter's IMEI. Acknowledges the rest if the protocol requires
+/// it, records any state they carry, routes them to the request waiting for them, if any, and
+/// publishes them to the event pipeline.
+async fn dispatch_frame(
+    imei: &str,
+    message: String,
+    connection: &Arc<Connection>,
+    state: &AppState,
+) {
+    let now = Utc::now();
+    if let Some(transition) = presence::record_seen(&state.presence, imei, now) {
+        presence::publish_transition(state, imei, transition);
+    }
+
+    let parsed = parse_command(&message);
+    // Frames are only acknowledged and recorded for the scooter that signed in on this connection
+    if let Ok(command) = &parsed {
+        if command.imei() != imei {
+            println!(
+                "Dropping frame from {} carrying IMEI {}: {}",
+                imei,
+                command.imei(),
+                message
+            );
+            return;
+        }
+    }
+
+    // Acknowledgements are not part of the command audit
+    if let Some(ack) = command_code(&message).and_then(|code| commands::generate_ack(imei, code)) {
+        if let Err(err) = write_command(connection, &ack).await {
+            println!("Failed to acknowledge frame from {}: {}", imei, err);
+        }
+    }
+
+    // Replies claimed by a waiting request still update the registry
+    if let Ok(command) = &parsed {
+        let status_change = registry::record(&state.registry, imei, command, now);
+
+        // Switch between the parked and riding tracking intervals
+        let tracking_status = match command {
+            ScooterCommand::SigningIn { .. } => Some(tracking::current_status(state, imei)),
+            _ => status_change,
+        };
+        if let Some(status) = tracking_status {
+            tracking::apply(state, imei, connection, &status).await;
+        }
+
+        // S7 settings do not survive a restart or an unlock
+        if matches!(
+            command,
+            ScooterCommand::SigningIn { .. }
+                | ScooterCommand::UnlockResponse {
+                    status: Status::Success,
+                    ..
+                }
+        ) {
+            desired_settings::apply(state, imei, connection.clone());
+        }
+        desired_settings::check_drift(state, imei, command);
+
+        if let Some(history) = &state.history {
+            history.record_frame(imei, command, now);
+        }
+
+        match command {
+            ScooterCommand::AlarmCommand { alarm_type, .. } => {
+                alarms::raise(state, imei, alarm_type, now);
+            }
+            // Lock replies are usually claimed by a request, so completions get their own events
+            ScooterCommand::UnlockResponse {
+                status: Status::Success,
+                ..
+            } => events::publish(
+                &state.events,
+                DeviceEvent::Unlocked {
+                    imei: imei.to_string(),
+                },
+            ),
+            ScooterCommand::LockResponse {
+                status: Status::Success,
+                cycling_time,
+                ..
+            } => events::publish(
+                &state.events,
+                DeviceEvent::Locked {
+                    imei: imei.to_string(),
+                    cycling_time: *cycling_time,
+                },
+            ),
+            _ => {}
+        }
+    }
+
+    let unclaimed = connection.pending.lock().unwrap().resolve(message);
+
+    // Claimed replies are published too, e.g. a D0 fix requested through /locate is still a
+    // position for webhooks and the event streams
+    match parsed {
+        Ok(command) => {
+            println!("Parsed message: {:?}", command);
+            events::publish(
+                &state.events,
+                DeviceEvent::Frame {
+                    imei: imei.to_string(),
+                    command,
+                },
+            );
+        }
+        // A waiting request reports replies it cannot parse itself
+        Err(err) if unclaimed.is_some() => {
+            println!("Failed to parse message from {}: {}", imei, err)
+        }
+        Err(_) => {}
+    }
+}
+
 fn extract_imei(message: &str) -> Option<String> {
     // Regex to extract IMEI from the Q0 message
     let regex = regex::Regex::new(r"^\*SCOR,[^,]+,(\d{15}),Q0,").ok()?;
@@ -46,117 +305,354 @@ fn extract_imei(message: &str) -> Option<String> {
         .and_then(|caps| caps.get(1).map(|m| m.as_str().to_string()))
 }
 
-pub async fn get_client_socket(
-    clients: &ClientMap,
-    imei: &str,
-) -> Result<tokio::sync::OwnedMutexGuard<tokio::net::TcpStream>, String> {
-    let clients_lock = clients.lock().await;
+pub async fn get_client(clients: &ClientMap, imei: &str) -> Result<Arc<Connection>, AppError> {
+    clients
+        .lock()
+        .await
+        .get(imei)
+        .cloned()
+        .ok_or_else(|| AppError::ClientNotFound(format!("Client with IMEI {} not found", imei)))
+}
 
-    if let Some(client) = clients_lock.get(imei) {
-        // Clone the Arc to ensure the returned OwnedMutexGuard is valid
-        let client_clone = client.clone();
-        drop(clients_lock); // Explicitly drop clients_lock to avoid holding the lock longer than necessary
-        Ok(client_clone.lock_owned().await)
-    } else {
-        Err(format!("Client with IMEI {} not found", imei))
-    }
+/// Writes `command` to the scooter without waiting for a reply, and records it in the command
+/// audit history if that is enabled.
+pub async fn send_command(connection: &Connection, command: &str) -> Result<(), AppError> {
+    let sent_at = Utc::now();
+    let result = write_command(connection, command).await;
+    audit(
+        connection,
+        command,
+        command_code(command).unwrap_or_default(),
+        sent_at,
+        result.as_ref().map(|()| None),
+    );
+    result
 }
 
-pub async fn send_command(socket: &mut tokio::net::TcpStream, command: &str) -> Result<(), String> {
-    socket
-        .write_all(command.as_bytes())
+/// Writes `command` to the scooter.
+///
+/// The write runs on its own task so a caller that is dropped mid-write, e.g. because the
+/// HTTP client went away, cannot leave half a frame on the wire or the writer locked.
+/// Waiting for the writer and the write itself are bounded by the write timeout; when that
+/// expires the scooter is not reading, so the connection is dropped.
+async fn write_command(connection: &Connection, command: &str) -> Result<(), AppError> {
+    let writer = connection.writer.clone();
+    let bytes = command.as_bytes().to_vec();
+    let write_timeout = connection.write_timeout;
+    let write = tokio::spawn(async move {
+        timeout(write_timeout, async {
+            writer.lock().await.write_all(&bytes).await
+        })
         .await
-        .map_err(|_| format!("Failed to send command: {}", command))
+    });
+
+    match write.await {
+        Ok(Ok(Ok(()))) => Ok(()),
+        Ok(Err(_)) => {
+            connection.stalled.notify_one();
+            Err(AppError::DeviceTimeout(format!(
+                "Write not completed within {}s: {}",
+                write_timeout.as_secs(),
+                command.trim_end()
+            )))
+        }
+        _ => Err(AppError::SocketError(format!(
+            "Failed to send command: {}",
+            command
+        ))),
+    }
 }
 
-pub async fn read_response(
-    socket: &mut tokio::net::TcpStream,
-    buffer: &mut [u8],
-) -> Result<String, String> {
-    let n = socket
-        .read(buffer)
-        .await
-        .map_err(|_| "Failed to read response".to_string())?;
-    Ok(String::from_utf8_lossy(&buffer[..n]).to_string())
+/// Removes a pending request from the table when its caller stops waiting for any reason.
+struct PendingGuard<'a> {
+    connection: &'a Connection,
+    code: &'a str,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.connection
+            .pending
+            .lock()
+            .unwrap()
+            .cancel(self.code, self.id);
+    }
+}
+
+/// Sends `command` and waits up to `timeout` for the `code` reply accepted by `matches`.
+///
+/// The request is registered before the command is written so a fast reply cannot be missed.
+/// Its outcome is recorded in the command audit history if that is enabled.
+pub async fn send_request(
+    connection: &Connection,
+    command: &str,
+    code: &str,
+    timeout: Duration,
+    matches: impl Fn(&str) -> bool + Send + 'static,
+) -> Result<String, AppError> {
+    let sent_at = Utc::now();
+    let result = exchange(connection, command, code, timeout, matches).await;
+    audit(
+        connection,
+        command,
+        code,
+        sent_at,
+        result.as_ref().map(|reply| Some(reply.as_str())),
+    );
+    result
+}
+
+/// Records a command sent at `sent_at` and its reply, if one was expected, in the command
+/// audit history.
+fn audit(
+    connection: &Connection,
+    command: &str,
+    code: &str,
+    sent_at: DateTime<Utc>,
+    result: Result<Option<&str>, &AppError>,
+) {
+    let Some(history) = &connection.history else {
+        return;
+    };
+
+    let (outcome, reply, error) = match result {
+        Ok(Some(reply)) => (
+            CommandOutcome::Replied,
+            Some(reply.trim_end().to_string()),
+            None,
+        ),
+        Ok(None) => (CommandOutcome::Sent, None, None),
+        Err(AppError::DeviceTimeout(err)) => (CommandOutcome::TimedOut, None, Some(err.clone())),
+        Err(err) => (CommandOutcome::Failed, None, Some(err.to_string())),
+    };
+    let record = CommandRecord {
+        recorded_at: sent_at,
+        code: code.to_string(),
+        command: command.trim_end().to_string(),
+        outcome,
+        reply,
+        error,
+        latency_ms: (Utc::now() - sent_at).num_milliseconds().max(0) as u64,
+    };
+    history.record_command(&connection.imei, record);
+}
+
+async fn exchange(
+    connection: &Connection,
+    command: &str,
+    code: &str,
+    timeout: Duration,
+    matches: impl Fn(&str) -> bool + Send + 'static,
+) -> Result<String, AppError> {
+    let (id, reply) = connection.pending.lock().unwrap().register(code, matches);
+    let _guard = PendingGuard {
+        connection,
+        code,
+        id,
+    };
+
+    write_command(connection, command).await?;
+
+    match tokio::time::timeout(timeout, reply).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(_)) => Err(AppError::SocketError(format!(
+            "Connection closed while waiting for {} response",
+            code
+        ))),
+        Err(_) => Err(AppError::DeviceTimeout(format!(
+            "No {} response within {}s",
+            code,
+            timeout.as_secs()
+        ))),
+    }
 }
 
-pub async fn handle_r0_response(
-    socket: &mut tokio::net::TcpStream,
+pub async fn request_r0(
+    connection: &Connection,
+    command: &str,
     imei: &str,
     r0_operation: &R0Operation,
     timestamp: i64,
-) -> Result<String, String> {
-    let mut buffer = vec![0; 1024];
-    loop {
-        let response = read_response(socket, &mut buffer).await?;
-        match protocol::validate_r0_response(&response, imei, r0_operation, USER_ID, timestamp) {
-            Ok(key) => return Ok(key),
-            Err(err) => {
-                println!("Ignored invalid R0 response: {} ({})", response, err);
-            }
+) -> Result<String, AppError> {
+    let expected_imei = imei.to_string();
+    let expected_operation = r0_operation.clone();
+    let timeout = config::command_timeout("R0");
+    let response = send_request(connection, command, "R0", timeout, move |frame| {
+        protocol::validate_r0_response(
+            frame,
+            &expected_imei,
+            &expected_operation,
+            USER_ID,
+            timestamp,
+        )
+        .is_ok()
+    })
+    .await?;
+
+    protocol::validate_r0_response(&response, imei, r0_operation, USER_ID, timestamp)
+        .map_err(|err| AppError::InvalidCommand(err.to_string()))
+}
+
+pub async fn request_l(
+    connection: &Connection,
+    command: &str,
+    imei: &str,
+    code: &'static str,
+    timestamp: Option<i64>,
+) -> Result<(), AppError> {
+    let expected_imei = imei.to_string();
+    let timeout = config::command_timeout(code);
+    let response = send_request(connection, command, code, timeout, move |frame| {
+        validate_l_response(frame, &expected_imei, code, timestamp).is_ok()
+    })
+    .await?;
+
+    println!("Valid {} response received: {}", code, response);
+    Ok(())
+}
+
+fn validate_l_response(
+    response: &str,
+    imei: &str,
+    code: &str,
+    timestamp: Option<i64>,
+) -> Result<(), ParseError> {
+    match (code, timestamp) {
+        ("L0", Some(timestamp)) => {
+            protocol::validate_l0_response(response, imei, USER_ID, timestamp)
         }
+        ("L1", _) => protocol::validate_l1_response(response, imei, USER_ID),
+        _ => Err(ParseError::UnknownCommand(code.to_string())),
     }
 }
 
-pub async fn handle_l_response(
-    socket: &mut tokio::net::TcpStream,
+/// Requests a single position fix and returns the scooter's D0 reply.
+pub async fn request_d0(
+    connection: &Connection,
+    command: &str,
     imei: &str,
+) -> Result<PositioningResponse, AppError> {
+    let expected_imei = imei.to_string();
+    let timeout = config::command_timeout("D0");
+    let response = send_request(connection, command, "D0", timeout, move |frame| {
+        matches!(
+            parse_command(frame),
+            Ok(ScooterCommand::PositioningResponse(PositioningResponse {
+                ref imei,
+                identifier: PositioningIdentifier::ObtainPositioning,
+                ..
+            })) if *imei == expected_imei
+        )
+    })
+    .await?;
+
+    match parse_command(&response) {
+        Ok(ScooterCommand::PositioningResponse(position)) => Ok(position),
+        _ => Err(AppError::InvalidCommand(response)),
+    }
+}
+
+/// Asks for an S6 status report and waits for it.
+pub async fn request_s6(
+    connection: &Connection,
     command: &str,
-    timestamp: Option<i64>,
-) -> Result<(), String> {
-    let mut buffer = vec![0; 1024];
-    loop {
-        let response = read_response(socket, &mut buffer).await?;
-        let validation_result = match command {
-            "L0" => protocol::validate_l0_response(&response, imei, USER_ID, timestamp.unwrap()),
-            "L1" => protocol::validate_l1_response(&response, imei, USER_ID),
-            _ => Err("Unknown command type"),
-        };
+    imei: &str,
+) -> Result<StatusReport, AppError> {
+    let expected_imei = imei.to_string();
+    let timeout = config::command_timeout("S6");
+    let response = send_request(connection, command, "S6", timeout, move |frame| {
+        matches!(
+            parse_command(frame),
+            Ok(ScooterCommand::StatusResponse(StatusReport { ref imei, .. })) if *imei == expected_imei
+        )
+    })
+    .await?;
 
-        match validation_result {
-            Ok(_) => {
-                println!("Valid {} response received: {}", command, response);
-                return Ok(());
-            }
-            Err(err) => {
-                println!(
-                    "Ignored invalid {} response: {} ({})",
-                    command, response, err
-                );
-            }
-        }
+    match parse_command(&response) {
+        Ok(ScooterCommand::StatusResponse(report)) => Ok(report),
+        _ => Err(AppError::InvalidCommand(response)),
     }
 }
 
-pub async fn handle_s7_response(
-    socket: &mut tokio::net::TcpStream,
+/// Sets the D1 tracking interval and waits for the scooter to confirm it.
+pub async fn request_d1(
+    connection: &Connection,
+    command: &str,
+    imei: &str,
+    interval: u16,
+) -> Result<(), AppError> {
+    let expected_imei = imei.to_string();
+    let timeout = config::command_timeout("D1");
+    let response = send_request(connection, command, "D1", timeout, move |frame| {
+        matches!(
+            parse_command(frame),
+            Ok(ScooterCommand::TrackingIntervalResponse { ref imei, interval: confirmed })
+                if *imei == expected_imei && confirmed == interval
+        )
+    })
+    .await?;
+
+    println!("Valid D1 response received: {}", response);
+    Ok(())
+}
+
+/// Plays `play_content` and waits for the scooter to echo it.
+pub async fn request_v0(
+    connection: &Connection,
+    command: &str,
+    imei: &str,
+    play_content: &BeepPlayContent,
+) -> Result<(), AppError> {
+    let expected_imei = imei.to_string();
+    let expected_content = play_content.clone();
+    let timeout = config::command_timeout("V0");
+    let response = send_request(connection, command, "V0", timeout, move |frame| {
+        matches!(
+            parse_command(frame),
+            Ok(ScooterCommand::BeepPlaybackCommand { ref imei, ref play_content })
+                if *imei == expected_imei && *play_content == expected_content
+        )
+    })
+    .await?;
+
+    println!("Valid V0 response received: {}", response);
+    Ok(())
+}
+
+/// Sends `settings` and waits for the scooter to echo them.
+pub async fn request_settings(
+    connection: &Connection,
+    imei: &str,
+    settings: &ScooterSettings,
+) -> Result<(), AppError> {
+    let command = commands::generate(desired_settings::command(imei, settings));
+    let expected_imei = imei.to_string();
+    let expected = settings.clone();
+    let timeout = config::command_timeout("S7");
+    let response = send_request(connection, &command, "S7", timeout, move |frame| {
+        protocol::validate_s7_response(frame, &expected_imei, &expected).is_ok()
+    })
+    .await?;
+
+    println!("Valid S7 response received: {}", response);
+    Ok(())
+}
+
+/// Sends the settings chosen in an API request; see [`request_settings`].
+pub async fn request_s7(
+    connection: &Connection,
     imei: &str,
     headlight_switch: &Turn,
     speed_mode: &SpeedMode,
     throttle_response: &Turn,
     taillights_flashing: &Turn,
-) -> Result<(), String> {
-    let mut buffer = vec![0; 1024];
-    loop {
-        let response = read_response(socket, &mut buffer).await?;
-
-        let validation_result = protocol::validate_s7_response(
-            &response,
-            imei,
-            headlight_switch.into(),
-            speed_mode.into(),
-            throttle_response.into(),
-            taillights_flashing.into(),
-        );
-
-        match validation_result {
-            Ok(_) => {
-                println!("Valid S7 response received: {}", response);
-                return Ok(());
-            }
-            Err(err) => {
-                println!("Ignored invalid S7 response: {} ({})", response, err);
-            }
-        }
-    }
+) -> Result<(), AppError> {
+    let settings = ScooterSettings::reported(
+        &headlight_switch.into(),
+        &speed_mode.into(),
+        &throttle_response.into(),
+        &taillights_flashing.into(),
+    );
+    request_settings(connection, imei, &settings).await
 }
@@ -0,0 +1,541 @@
+//! On-disk telemetry history in SQLite, enabled with `HISTORY_DB_PATH`.
+//!
+//! H0 heartbeats, effective D0 fixes, W0 alarms and the outcome of every command sent to a
+//! scooter, other than automatic acknowledgements, are kept for `HISTORY_RETENTION_DAYS`.
+//! Timestamps are stored as Unix milliseconds.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, types::Type, Connection, OpenFlags, Row};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::commands::{
+    alarm_command::AlarmType,
+    hearbeat_command::{ChargingStatus, ScooterStatus},
+    positioning_command::PositioningStatus,
+    scooter_command::ScooterCommand,
+};
+
+use super::alarms::Severity;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS heartbeats (
+    imei TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    voltage REAL NOT NULL,
+    signal INTEGER NOT NULL,
+    power INTEGER NOT NULL,
+    charging TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS heartbeats_by_device ON heartbeats (imei, recorded_at);
+
+CREATE TABLE IF NOT EXISTS positions (
+    imei TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    fixed_at INTEGER NOT NULL,
+    latitude REAL NOT NULL,
+    longitude REAL NOT NULL,
+    satellites INTEGER NOT NULL,
+    hdop REAL NOT NULL,
+    altitude REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS positions_by_device ON positions (imei, recorded_at);
+
+CREATE TABLE IF NOT EXISTS alarms (
+    imei TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    alarm_type TEXT NOT NULL,
+    severity TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS alarms_by_device ON alarms (imei, recorded_at);
+
+CREATE TABLE IF NOT EXISTS commands (
+    imei TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    command TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    reply TEXT,
+    error TEXT,
+    latency_ms INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS commands_by_device ON commands (imei, recorded_at);
+";
+
+const TABLES: [&str; 4] = ["heartbeats", "positions", "alarms", "commands"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeartbeatRecord {
+    pub recorded_at: DateTime<Utc>, // RFC 3339
+    pub status: ScooterStatus,
+    /// Battery voltage in volts.
+    pub voltage: f32,
+    pub signal: u8,
+    /// Battery level in percent.
+    pub power: u8,
+    pub charging: ChargingStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionRecord {
+    pub recorded_at: DateTime<Utc>, // RFC 3339
+    /// GPS time of the fix.
+    pub fixed_at: DateTime<Utc>, // RFC 3339
+    pub latitude: f64,
+    pub longitude: f64,
+    pub satellites: u8,
+    pub hdop: f32,
+    /// Metres above sea level.
+    pub altitude: f32,
+}
+
+/// A W0 frame. Repeats are stored too, unlike the deduplicated alarms in the device state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlarmRecord {
+    pub recorded_at: DateTime<Utc>, // RFC 3339
+    pub alarm_type: AlarmType,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandOutcome {
+    /// Written to the scooter, which does not reply to it.
+    Sent,
+    /// The scooter sent the expected reply.
+    Replied,
+    TimedOut,
+    /// The command could not be sent or the connection closed before the reply.
+    Failed,
+}
+
+/// Audit record of a command sent to a scooter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandRecord {
+    /// When the command was sent.
+    pub recorded_at: DateTime<Utc>, // RFC 3339
+    pub code: String,
+    pub command: String,
+    pub outcome: CommandOutcome,
+    pub reply: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+/// A queued write, run on the writer thread.
+type Write = Box<dyn FnOnce(&Connection) + Send>;
+
+/// Handle to the history database, shared by all connections.
+///
+/// Writes, including pruning, are queued to a single writer thread that owns the read-write
+/// connection, so that recording never holds up a connection. Queries run on the blocking
+/// thread pool over a separate read-only connection and do not wait for the writer.
+#[derive(Clone)]
+pub struct History {
+    reader: Arc<Mutex<Connection>>,
+    writes: mpsc::Sender<Write>,
+    /// Writes dropped because the queue was full since the last retention run.
+    dropped: Arc<AtomicU64>,
+}
+
+impl History {
+    /// Opens or creates the database at `path`.
+    pub fn open(path: &str) -> rusqlite::Result<Self> {
+        let writer = Connection::open(path)?;
+        // Queries read a snapshot instead of waiting for the writer
+        writer.pragma_update(None, "journal_mode", "WAL")?;
+        writer.execute_batch(SCHEMA)?;
+        let reader = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Ok(Self::with_connections(writer, reader))
+    }
+
+    /// A history that lives only as long as the process.
+    pub fn in_memory() -> rusqlite::Result<Self> {
+        // Both connections share one private in-memory database
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let uri = format!(
+            "file:history-{}?mode=memory&cache=shared",
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        );
+        let flags = OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let writer = Connection::open_with_flags(
+            &uri,
+            flags | OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+        )?;
+        writer.execute_batch(SCHEMA)?;
+        let reader = Connection::open_with_flags(&uri, flags | OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Ok(Self::with_connections(writer, reader))
+    }
+
+    fn with_connections(writer: Connection, reader: Connection) -> Self {
+        // The writer stops once every handle is dropped
+        let (writes, mut queue) = mpsc::channel::<Write>(crate::config::HISTORY_WRITE_QUEUE_SIZE);
+        std::thread::spawn(move || {
+            while let Some(write) = queue.blocking_recv() {
+                write(&writer);
+            }
+        });
+
+        Self {
+            reader: Arc::new(Mutex::new(reader)),
+            writes,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Queues the telemetry in `command` for storage if it is an H0 heartbeat, an effective D0
+    /// fix or a W0 alarm.
+    pub fn record_frame(&self, imei: &str, command: &ScooterCommand, now: DateTime<Utc>) {
+        let recorded_at = now.timestamp_millis();
+        let owner = imei.to_string();
+        let what = format!("{} telemetry", imei);
+        match command.clone() {
+            ScooterCommand::HeartBeat {
+                status,
+                voltage,
+                signal,
+                power,
+                charging,
+                ..
+            } => {
+                self.write(what, move |db| {
+                    db.execute(
+                        "INSERT INTO heartbeats (imei, recorded_at, status, voltage, signal, power, charging)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            owner,
+                            recorded_at,
+                            to_text(&status),
+                            voltage,
+                            signal,
+                            power,
+                            to_text(&charging)
+                        ],
+                    )
+                })
+            }
+            ScooterCommand::PositioningResponse(position)
+                if position.positioning_status == PositioningStatus::Effective =>
+            {
+                self.write(what, move |db| {
+                    db.execute(
+                        "INSERT INTO positions (imei, recorded_at, fixed_at, latitude, longitude, satellites, hdop, altitude)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            owner,
+                            recorded_at,
+                            position.utc_datetime.timestamp_millis(),
+                            position.latitude,
+                            position.longitude,
+                            position.satellites_number,
+                            position.positioning_accuracy,
+                            position.altitude
+                        ],
+                    )
+                })
+            }
+            ScooterCommand::AlarmCommand { alarm_type, .. } => {
+                let severity = Severity::from(&alarm_type);
+                self.write(what, move |db| {
+                    db.execute(
+                        "INSERT INTO alarms (imei, recorded_at, alarm_type, severity)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        params![owner, recorded_at, to_text(&alarm_type), to_text(&severity)],
+                    )
+                })
+            }
+            _ => {}
+        }
+    }
+
+    /// Queues the audit record of a command sent to `imei`.
+    pub fn record_command(&self, imei: &str, record: CommandRecord) {
+        let imei = imei.to_string();
+        self.write("command audit record".to_string(), move |db| {
+            db.execute(
+                "INSERT INTO commands (imei, recorded_at, code, command, outcome, reply, error, latency_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    imei,
+                    record.recorded_at.timestamp_millis(),
+                    record.code,
+                    record.command,
+                    to_text(&record.outcome),
+                    record.reply,
+                    record.error,
+                    record.latency_ms
+                ],
+            )
+        });
+    }
+
+    /// Waits until every write queued so far has been stored.
+    pub async fn flush(&self) {
+        let _ = self.on_writer(|_| Ok(())).await;
+    }
+
+    /// Number of writes dropped because the queue was full since the last call.
+    pub fn take_dropped_writes(&self) -> u64 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+
+    /// Heartbeats from `imei` recorded in `[from, to]`, oldest first.
+    pub async fn heartbeats(
+        &self,
+        imei: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> rusqlite::Result<Vec<HeartbeatRecord>> {
+        self.query(
+            "SELECT recorded_at, status, voltage, signal, power, charging FROM heartbeats",
+            imei,
+            from,
+            to,
+            limit,
+            |row| {
+                Ok(HeartbeatRecord {
+                    recorded_at: to_time(row.get(0)?),
+                    status: from_text(row, 1)?,
+                    voltage: row.get(2)?,
+                    signal: row.get(3)?,
+                    power: row.get(4)?,
+                    charging: from_text(row, 5)?,
+                })
+            },
+        )
+        .await
+    }
+
+    pub async fn positions(
+        &self,
+        imei: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> rusqlite::Result<Vec<PositionRecord>> {
+        self.query(
+            "SELECT recorded_at, fixed_at, latitude, longitude, satellites, hdop, altitude FROM positions",
+            imei,
+            from,
+            to,
+            limit,
+            |row| {
+                Ok(PositionRecord {
+                    recorded_at: to_time(row.get(0)?),
+                    fixed_at: to_time(row.get(1)?),
+                    latitude: row.get(2)?,
+                    longitude: row.get(3)?,
+                    satellites: row.get(4)?,
+                    hdop: row.get(5)?,
+                    altitude: row.get(6)?,
+                })
+            },
+        )
+        .await
+    }
+
+    pub async fn alarms(
+        &self,
+        imei: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> rusqlite::Result<Vec<AlarmRecord>> {
+        self.query(
+            "SELECT recorded_at, alarm_type, severity FROM alarms",
+            imei,
+            from,
+            to,
+            limit,
+            |row| {
+                Ok(AlarmRecord {
+                    recorded_at: to_time(row.get(0)?),
+                    alarm_type: from_text(row, 1)?,
+                    severity: from_text(row, 2)?,
+                })
+            },
+        )
+        .await
+    }
+
+    pub async fn commands(
+        &self,
+        imei: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+    ) -> rusqlite::Result<Vec<CommandRecord>> {
+        self.query(
+            "SELECT recorded_at, code, command, outcome, reply, error, latency_ms FROM commands",
+            imei,
+            from,
+            to,
+            limit,
+            |row| {
+                Ok(CommandRecord {
+                    recorded_at: to_time(row.get(0)?),
+                    code: row.get(1)?,
+                    command: row.get(2)?,
+                    outcome: from_text(row, 3)?,
+                    reply: row.get(4)?,
+                    error: row.get(5)?,
+                    latency_ms: row.get(6)?,
+                })
+            },
+        )
+        .await
+    }
+
+    /// Deletes everything recorded before `cutoff` and returns the number of rows removed.
+    pub async fn prune(&self, cutoff: DateTime<Utc>) -> rusqlite::Result<usize> {
+        let cutoff = cutoff.timestamp_millis();
+        self.on_writer(move |db| {
+            TABLES.iter().try_fold(0, |removed, table| {
+                let sql = format!("DELETE FROM {} WHERE recorded_at < ?1", table);
+                Ok(removed + db.execute(&sql, [cutoff])?)
+            })
+        })
+        .await
+    }
+
+    /// Runs `select` for one device and time range, appending the filter and ordering.
+    async fn query<T: Send + 'static>(
+        &self,
+        select: &'static str,
+        imei: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: u32,
+        read: fn(&Row) -> rusqlite::Result<T>,
+    ) -> rusqlite::Result<Vec<T>> {
+        let imei = imei.to_string();
+        self.run(move |db| {
+            let sql = format!(
+                "{} WHERE imei = ?1 AND recorded_at BETWEEN ?2 AND ?3 ORDER BY recorded_at, rowid LIMIT ?4",
+                select
+            );
+            let mut statement = db.prepare_cached(&sql)?;
+            let rows = statement.query_map(
+                params![imei, from.timestamp_millis(), to.timestamp_millis(), limit],
+                read,
+            )?;
+            rows.collect()
+        })
+        .await
+    }
+
+    /// Queues `call` for the writer thread without waiting for it. The write is dropped if the
+    /// queue is full; the first drop is logged and the count is reported by `run_retention`.
+    pub(crate) fn write(
+        &self,
+        what: String,
+        call: impl FnOnce(&Connection) -> rusqlite::Result<usize> + Send + 'static,
+    ) {
+        let write: Write = Box::new(move |db| {
+            if let Err(err) = call(db) {
+                eprintln!("Failed to store {}: {}", what, err);
+            }
+        });
+        if self.writes.try_send(write).is_err() && self.dropped.fetch_add(1, Ordering::Relaxed) == 0
+        {
+            eprintln!("History write queue is full, dropping writes");
+        }
+    }
+
+    /// Runs `call` on the writer thread after the writes queued before it, and waits for it.
+    async fn on_writer<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+    ) -> rusqlite::Result<T> {
+        let (done, result) = oneshot::channel();
+        let write: Write = Box::new(move |db| {
+            let _ = done.send(call(db));
+        });
+        // The writer outlives every handle, so it only goes away if a write panicked
+        let stopped = "history writer stopped";
+        self.writes.send(write).await.expect(stopped);
+        result.await.expect(stopped)
+    }
+
+    /// Runs a query on the read-only connection. SQLite calls block, so they run on the
+    /// blocking thread pool.
+    async fn run<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+    ) -> rusqlite::Result<T> {
+        let reader = self.reader.clone();
+        tokio::task::spawn_blocking(move || call(&reader.lock().unwrap()))
+            .await
+            .expect("history database call panicked")
+    }
+}
+
+/// Opens the database configured with `HISTORY_DB_PATH`, or returns `None` when history is off
+/// or the database cannot be opened.
+pub fn from_env() -> Option<History> {
+    let path = crate::config::history_db_path()?;
+    match History::open(&path) {
+        Ok(history) => {
+            println!("Recording telemetry history in {}", path);
+            Some(history)
+        }
+        Err(err) => {
+            eprintln!("Failed to open history database {}: {}", path, err);
+            None
+        }
+    }
+}
+
+/// Deletes records older than `retention` once per `HISTORY_PRUNE_INTERVAL`.
+pub async fn run_retention(history: History, retention: Duration) {
+    let mut interval = tokio::time::interval(crate::config::HISTORY_PRUNE_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let cutoff = Utc::now() - retention;
+        match history.prune(cutoff).await {
+            Ok(0) => {}
+            Ok(removed) => println!("Pruned {} history records before {}", removed, cutoff),
+            Err(err) => eprintln!("Failed to prune history: {}", err),
+        }
+
+        match history.take_dropped_writes() {
+            0 => {}
+            dropped => eprintln!(
+                "Dropped {} history writes because the write queue was full",
+                dropped
+            ),
+        }
+    }
+}
+
+/// The snake_case name a unit enum is serialized with.
+fn to_text<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn from_text<T: DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let text: String = row.get(index)?;
+    serde_json::from_value(serde_json::Value::String(text))
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(err)))
+}
+
+fn to_time(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
@@ -0,0 +1,151 @@
+use std::future::Future;
+
+use crate::server::{history::History, AppState};
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Time range of a history query, as RFC 3339 timestamps. Both ends are inclusive.
+#[derive(Default, Deserialize)]
+pub struct HistoryQuery {
+    /// Defaults to `HISTORY_DEFAULT_RANGE` before `to`.
+    pub from: Option<DateTime<Utc>>,
+    /// Defaults to now.
+    pub to: Option<DateTime<Utc>>,
+    /// Maximum number of records, oldest first. Capped at `HISTORY_MAX_QUERY_LIMIT`.
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct HistoryResponse<T> {
+    pub success: bool,
+    pub message: String,
+    pub imei: String,
+    pub from: DateTime<Utc>, // RFC 3339
+    pub to: DateTime<Utc>,   // RFC 3339
+    pub records: Vec<T>,
+}
+
+pub async fn heartbeat_history_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    respond(state.history, imei, query, |history, imei, from, to, limit| async move {
+        history.heartbeats(&imei, from, to, limit).await
+    })
+    .await
+}
+
+pub async fn position_history_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    respond(state.history, imei, query, |history, imei, from, to, limit| async move {
+        history.positions(&imei, from, to, limit).await
+    })
+    .await
+}
+
+pub async fn alarm_history_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    respond(state.history, imei, query, |history, imei, from, to, limit| async move {
+        history.alarms(&imei, from, to, limit).await
+    })
+    .await
+}
+
+pub async fn command_history_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    respond(state.history, imei, query, |history, imei, from, to, limit| async move {
+        history.commands(&imei, from, to, limit).await
+    })
+    .await
+}
+
+/// Resolves the query defaults, runs `fetch` and wraps its records in a response.
+async fn respond<T, F, Fut>(
+    history: Option<History>,
+    imei: String,
+    query: HistoryQuery,
+    fetch: F,
+) -> (StatusCode, Json<HistoryResponse<T>>)
+where
+    F: FnOnce(History, String, DateTime<Utc>, DateTime<Utc>, u32) -> Fut,
+    Fut: Future<Output = rusqlite::Result<Vec<T>>>,
+{
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query
+        .from
+        .unwrap_or(to - crate::config::HISTORY_DEFAULT_RANGE);
+    let limit = query
+        .limit
+        .unwrap_or(crate::config::HISTORY_QUERY_LIMIT)
+        .min(crate::config::HISTORY_MAX_QUERY_LIMIT);
+
+    let failure = |status: StatusCode, message: String, imei: String| {
+        (
+            status,
+            Json(HistoryResponse {
+                success: false,
+                message,
+                imei,
+                from,
+                to,
+                records: Vec::new(),
+            }),
+        )
+    };
+
+    let Some(history) = history else {
+        return failure(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Telemetry history is disabled, set HISTORY_DB_PATH to record it".to_string(),
+            imei,
+        );
+    };
+    if from > to {
+        return failure(
+            StatusCode::BAD_REQUEST,
+            "`from` must not be after `to`".to_string(),
+            imei,
+        );
+    }
+
+    match fetch(history, imei.clone(), from, to, limit).await {
+        Ok(records) => {
+            let message = if records.len() as u32 == limit {
+                format!("{} records, limit reached", records.len())
+            } else {
+                format!("{} records", records.len())
+            };
+            (
+                StatusCode::OK,
+                Json(HistoryResponse {
+                    success: true,
+                    message,
+                    imei,
+                    from,
+                    to,
+                    records,
+                }),
+            )
+        }
+        Err(err) => failure(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read history: {}", err),
+            imei,
+        ),
+    }
+}
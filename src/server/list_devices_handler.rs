@@ -0,0 +1,88 @@
+use crate::{
+    commands::hearbeat_command::{ChargingStatus, ScooterStatus},
+    server::{
+        device_handler::{device_summaries, DeviceSummary},
+        presence::Connectivity,
+        AppState,
+    },
+};
+use axum::{
+    extract::{Json, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+/// Filters for `GET /devices`. Omitted filters match every scooter.
+#[derive(Debug, Default, Deserialize)]
+pub struct DevicesQuery {
+    pub online: Option<bool>,
+    pub locked: Option<bool>,
+    /// Only scooters whose last reported battery level is below this percentage.
+    pub battery_below: Option<u8>,
+    pub charging: Option<bool>,
+}
+
+impl DevicesQuery {
+    pub fn matches(&self, device: &DeviceSummary) -> bool {
+        let state = device.state.as_ref();
+
+        if let Some(online) = self.online {
+            if (device.connectivity == Some(Connectivity::Online)) != online {
+                return false;
+            }
+        }
+        if let Some(locked) = self.locked {
+            let status = state.and_then(|state| state.status.as_ref());
+            let expected = if locked {
+                ScooterStatus::Locked
+            } else {
+                ScooterStatus::Unlocked
+            };
+            if status != Some(&expected) {
+                return false;
+            }
+        }
+        if let Some(threshold) = self.battery_below {
+            match state.and_then(|state| state.power) {
+                Some(power) if power < threshold => {}
+                _ => return false,
+            }
+        }
+        if let Some(charging) = self.charging {
+            let status = state.and_then(|state| state.charging.as_ref());
+            if (status == Some(&ChargingStatus::Charging)) != charging {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[derive(Serialize)]
+pub struct DevicesResponse {
+    pub success: bool,
+    pub message: String,
+    pub devices: Vec<DeviceSummary>,
+}
+
+pub async fn list_devices_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DevicesQuery>,
+) -> impl IntoResponse {
+    let devices: Vec<DeviceSummary> = device_summaries(&state)
+        .await
+        .into_iter()
+        .filter(|device| query.matches(device))
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(DevicesResponse {
+            success: true,
+            message: format!("{} devices", devices.len()),
+            devices,
+        }),
+    )
+}
@@ -0,0 +1,106 @@
+use crate::{
+    commands::positioning_command::PositioningStatus,
+    server::{
+        commands,
+        handler::*,
+        registry::{self, Position},
+        AppState,
+    },
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct LocateRequest {
+    pub imei: String,
+}
+
+#[derive(Serialize)]
+pub struct LocateResponse {
+    pub success: bool,
+    pub message: String,
+    pub imei: String,
+    pub position: Option<Position>,
+    /// Set when the scooter had no fix and `position` is the last known one instead.
+    pub stale: bool,
+}
+
+pub async fn locate_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<LocateRequest>,
+) -> impl IntoResponse {
+    let imei = payload.imei.clone();
+
+    let connection = match get_client(&state.clients, &imei).await {
+        Ok(connection) => connection,
+        Err(err) => {
+            return (
+                err.status_code(),
+                Json(LocateResponse {
+                    success: false,
+                    message: err.to_string(),
+                    imei,
+                    position: None,
+                    stale: false,
+                }),
+            );
+        }
+    };
+
+    let d0_command = commands::generate_d0_command(&imei);
+    let response = match request_d0(&connection, &d0_command, &imei).await {
+        Ok(response) => response,
+        Err(err) => {
+            return (
+                err.status_code(),
+                Json(LocateResponse {
+                    success: false,
+                    message: err.to_string(),
+                    imei,
+                    position: None,
+                    stale: false,
+                }),
+            );
+        }
+    };
+
+    if response.positioning_status == PositioningStatus::Effective {
+        return (
+            StatusCode::OK,
+            Json(LocateResponse {
+                success: true,
+                message: "Position fix received".to_string(),
+                imei,
+                position: Some(registry::position(&response)),
+                stale: false,
+            }),
+        );
+    }
+
+    match registry::get(&state.registry, &imei).and_then(|device| device.position) {
+        Some(position) => (
+            StatusCode::OK,
+            Json(LocateResponse {
+                success: true,
+                message: "Scooter has no fix, returning last known position".to_string(),
+                imei,
+                position: Some(position),
+                stale: true,
+            }),
+        ),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(LocateResponse {
+                success: false,
+                message: "Scooter has no fix and no earlier position is known".to_string(),
+                imei,
+                position: None,
+                stale: false,
+            }),
+        ),
+    }
+}
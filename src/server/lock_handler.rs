@@ -1,4 +1,5 @@
 use crate::{
+    commands::unlock_command::R0Operation,
     config::USER_ID,
     server::ClientMap,
     server::{commands, handler::*},
@@ -28,17 +29,17 @@ pub async fn lock_handler(
     Json(payload): Json<LockRequest>,
 ) -> impl IntoResponse {
     let imei = payload.imei.clone();
-    let r0_operation = commands::R0Operation::Lock;
+    let r0_operation = R0Operation::Lock;
     let timestamp = timestamp::current();
 
-    let mut socket = match get_client_socket(&clients, &imei).await {
-        Ok(socket) => socket,
+    let connection = match get_client(&clients, &imei).await {
+        Ok(connection) => connection,
         Err(err) => {
             return (
-                StatusCode::NOT_FOUND,
+                err.status_code(),
                 Json(LockResponse {
                     success: false,
-                    message: err,
+                    message: err.to_string(),
                     imei,
                 }),
             );
@@ -46,25 +47,14 @@ pub async fn lock_handler(
     };
 
     let r0_command = commands::generate_r0_command(&imei, &r0_operation, 20, USER_ID, timestamp);
-    if let Err(err) = send_command(&mut socket, &r0_command).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(LockResponse {
-                success: false,
-                message: err,
-                imei,
-            }),
-        );
-    }
-
-    let r0_key = match handle_r0_response(&mut socket, &imei, &r0_operation, timestamp).await {
+    let r0_key = match request_r0(&connection, &r0_command, &imei, &r0_operation, timestamp).await {
         Ok(key) => key,
         Err(err) => {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                err.status_code(),
                 Json(LockResponse {
                     success: false,
-                    message: err,
+                    message: err.to_string(),
                     imei,
                 }),
             );
@@ -72,35 +62,12 @@ pub async fn lock_handler(
     };
 
     let l1_command = commands::generate_l1_command(&imei, &r0_key);
-    if let Err(err) = send_command(&mut socket, &l1_command).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(LockResponse {
-                success: false,
-                message: err,
-                imei,
-            }),
-        );
-    }
-
-    if let Err(err) = handle_l_response(&mut socket, &imei, "L1", None).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(LockResponse {
-                success: false,
-                message: err,
-                imei,
-            }),
-        );
-    }
-
-    let final_ack = commands::generate_l1_ack(&imei);
-    if let Err(err) = send_command(&mut socket, &final_ack).await {
+    if let Err(err) = request_l(&connection, &l1_command, &imei, "L1", None).await {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            err.status_code(),
             Json(LockResponse {
                 success: false,
-                message: err,
+                message: err.to_string(),
                 imei,
             }),
         );
@@ -1,24 +1,130 @@
-use handler::handle_connection;
+use axum::extract::FromRef;
+use desired_settings::SettingsOverrides;
+use events::EventSender;
+use handler::{handle_connection, Connection};
+use history::History;
+use presence::{HeartbeatPolicy, PresenceMap};
+use registry::{DeviceRegistry, ScooterSettings};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tokio::net::TcpStream;
 use tokio::sync::Mutex;
+use tracking::{TrackingOverrides, TrackingPolicy};
+use webhooks::{DeadLetters, RetryPolicy, WebhookMap};
 
+pub mod alarms;
+pub mod beep_handler;
 pub mod change_gear_handler;
 pub mod change_headlight_handler;
+pub mod codec;
 pub mod command_enums;
 pub mod commands;
+pub mod connectivity_handler;
+pub mod desired_settings;
+pub mod desired_settings_handler;
+pub mod device_handler;
+pub mod dispatcher;
+pub mod event_stream_handler;
+pub mod events;
 pub mod handler;
+pub mod history;
+pub mod history_handler;
+pub mod list_devices_handler;
+pub mod locate_handler;
 pub mod lock_handler;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod parser_service;
+pub mod presence;
 pub mod protocol;
-pub mod scooter_command;
+pub mod registry;
+pub mod settings_handler;
+pub mod status_handler;
+pub mod status_poll;
 pub mod tests;
+pub mod tracking;
+pub mod tracking_handler;
 pub mod unlock_handler;
+pub mod webhooks;
+pub mod webhooks_handler;
 
-pub type ClientMap = Arc<Mutex<HashMap<String, Arc<Mutex<TcpStream>>>>>;
+pub type ClientMap = Arc<Mutex<HashMap<String, Arc<Connection>>>>;
 
-pub async fn start_server(address: &str, clients: ClientMap) -> std::io::Result<()> {
+#[derive(Clone)]
+pub struct AppState {
+    pub clients: ClientMap,
+    pub events: EventSender,
+    /// Connections that send nothing for this long are considered dead and evicted.
+    pub idle_timeout: Duration,
+    /// Writes to a scooter that take longer than this drop its connection.
+    pub write_timeout: Duration,
+    pub presence: PresenceMap,
+    pub heartbeat_policy: HeartbeatPolicy,
+    pub registry: DeviceRegistry,
+    /// Default D1 tracking intervals, used where a scooter has no override in `tracking`.
+    pub tracking_policy: TrackingPolicy,
+    pub tracking: TrackingOverrides,
+    /// Fleet-wide S7 settings, re-sent after every sign-in and unlock.
+    pub settings_policy: ScooterSettings,
+    pub desired_settings: SettingsOverrides,
+    /// Repeats of an alarm within this window are folded into the first one.
+    pub alarm_window: Duration,
+    pub webhooks: WebhookMap,
+    pub webhook_retry: RetryPolicy,
+    pub dead_letters: DeadLetters,
+    /// Telemetry and command audit database, `None` unless `HISTORY_DB_PATH` is set.
+    pub history: Option<History>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self {
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            events: events::channel(),
+            idle_timeout: crate::config::idle_timeout(),
+            write_timeout: crate::config::write_timeout(),
+            presence: PresenceMap::default(),
+            heartbeat_policy: HeartbeatPolicy::default(),
+            registry: DeviceRegistry::default(),
+            tracking_policy: TrackingPolicy::from_env(),
+            tracking: TrackingOverrides::default(),
+            settings_policy: desired_settings::from_env(),
+            desired_settings: SettingsOverrides::default(),
+            alarm_window: crate::config::alarm_window(),
+            webhooks: WebhookMap::default(),
+            webhook_retry: RetryPolicy::from_env(),
+            dead_letters: DeadLetters::default(),
+            history: history::from_env(),
+        }
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromRef<AppState> for ClientMap {
+    fn from_ref(state: &AppState) -> Self {
+        state.clients.clone()
+    }
+}
+
+impl FromRef<AppState> for PresenceMap {
+    fn from_ref(state: &AppState) -> Self {
+        state.presence.clone()
+    }
+}
+
+impl FromRef<AppState> for DeviceRegistry {
+    fn from_ref(state: &AppState) -> Self {
+        state.registry.clone()
+    }
+}
+
+pub async fn start_server(address: &str, state: AppState) -> std::io::Result<()> {
     let listener: TcpListener = TcpListener::bind(address).await?;
     println!("Server running on {}", address);
 
@@ -26,9 +132,9 @@ pub async fn start_server(address: &str, clients: ClientMap) -> std::io::Result<
         let (socket, addr) = listener.accept().await?;
         println!("Accepted connection from {}", addr);
 
-        let clients = clients.clone();
+        let state = state.clone();
         tokio::spawn(async move {
-            if let Err(e) = handle_connection(socket, clients).await {
+            if let Err(e) = handle_connection(socket, state).await {
                 eprintln!("Error handling connection: {}", e);
             }
         });
@@ -0,0 +1,212 @@
+//! Optional MQTT bridge, enabled with the `mqtt` feature and `MQTT_HOST`.
+//!
+//! Device events are published to `scooters/{imei}/{kind}`, e.g. `scooters/{imei}/heartbeat`.
+//! Messages on `scooters/{imei}/cmd/{unlock|lock|gear|light}` run the matching REST handler, and
+//! its JSON response is published to `scooters/{imei}/cmd/{action}/reply`.
+
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::commands::scooter_command::ScooterCommand;
+
+use super::{
+    change_gear_handler::change_gear_handler, change_headlight_handler::change_headlight_handler,
+    events::DeviceEvent, lock_handler::lock_handler, unlock_handler::unlock_handler, AppState,
+};
+
+pub const TOPIC_PREFIX: &str = "scooters";
+
+/// The topic `event` is published to, or `None` if it is not bridged.
+pub fn event_topic(event: &DeviceEvent) -> Option<String> {
+    let kind = match event {
+        DeviceEvent::Frame { command, .. } => match command {
+            ScooterCommand::SigningIn { .. } => "sign_in",
+            ScooterCommand::HeartBeat { .. } => "heartbeat",
+            ScooterCommand::PositioningResponse(_) => "position",
+            ScooterCommand::StatusResponse(_) => "status",
+            ScooterCommand::ScooterSetting { .. } => "settings",
+            ScooterCommand::TrackingIntervalResponse { .. } => "tracking",
+            // Raw W0 frames are covered by the deduplicated `alarm` event
+            _ => return None,
+        },
+        DeviceEvent::Alarm { .. } => "alarm",
+        DeviceEvent::Unlocked { .. } => "unlocked",
+        DeviceEvent::Locked { .. } => "locked",
+        DeviceEvent::Disconnected { .. } => "disconnected",
+        DeviceEvent::ConnectivityChanged { .. } => "connectivity",
+        DeviceEvent::SettingsDrift { .. } => "settings_drift",
+    };
+    Some(format!("{}/{}/{}", TOPIC_PREFIX, event.imei(), kind))
+}
+
+/// Splits `scooters/{imei}/cmd/{action}` into the IMEI and action.
+pub fn parse_command_topic(topic: &str) -> Option<(&str, &str)> {
+    match topic.split('/').collect::<Vec<_>>()[..] {
+        [TOPIC_PREFIX, imei, "cmd", action] if !imei.is_empty() => Some((imei, action)),
+        _ => None,
+    }
+}
+
+pub fn reply_topic(imei: &str, action: &str) -> String {
+    format!("{}/{}/cmd/{}/reply", TOPIC_PREFIX, imei, action)
+}
+
+/// Runs `action` through the same handler as the REST API and returns its JSON response with
+/// the HTTP status added as `status`.
+///
+/// `payload` is the handler's JSON request without the IMEI, e.g. `{"gear":2}` for `gear` or
+/// `{"state":true}` for `light`. A `request_id` in it is echoed in the reply.
+pub async fn run_command(state: &AppState, imei: &str, action: &str, payload: &[u8]) -> Value {
+    let mut request = if payload.iter().all(u8::is_ascii_whitespace) {
+        json!({})
+    } else {
+        match serde_json::from_slice::<Value>(payload) {
+            Ok(request @ Value::Object(_)) => request,
+            _ => {
+                return reply(
+                    StatusCode::BAD_REQUEST,
+                    "Payload must be a JSON object",
+                    None,
+                )
+            }
+        }
+    };
+    let request_id = request.get("request_id").cloned();
+    request["imei"] = json!(imei);
+
+    let clients = State(state.clients.clone());
+    let response = match action {
+        "unlock" => match parse(request) {
+            Ok(request) => unlock_handler(clients, request).await.into_response(),
+            Err(err) => return reply(StatusCode::BAD_REQUEST, &err, request_id),
+        },
+        "lock" => match parse(request) {
+            Ok(request) => lock_handler(clients, request).await.into_response(),
+            Err(err) => return reply(StatusCode::BAD_REQUEST, &err, request_id),
+        },
+        "gear" => match parse(request) {
+            Ok(request) => change_gear_handler(clients, request).await.into_response(),
+            Err(err) => return reply(StatusCode::BAD_REQUEST, &err, request_id),
+        },
+        "light" => match parse(request) {
+            Ok(request) => change_headlight_handler(clients, request)
+                .await
+                .into_response(),
+            Err(err) => return reply(StatusCode::BAD_REQUEST, &err, request_id),
+        },
+        _ => {
+            let message = format!("Unknown command: {}", action);
+            return reply(StatusCode::NOT_FOUND, &message, request_id);
+        }
+    };
+
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok());
+    let mut body = body.unwrap_or_else(|| json!({}));
+    body["status"] = json!(status.as_u16());
+    if let Some(request_id) = request_id {
+        body["request_id"] = request_id;
+    }
+    body
+}
+
+fn parse<T: DeserializeOwned>(request: Value) -> Result<Json<T>, String> {
+    serde_json::from_value(request)
+        .map(Json)
+        .map_err(|err| format!("Invalid payload: {}", err))
+}
+
+fn reply(status: StatusCode, message: &str, request_id: Option<Value>) -> Value {
+    let mut body = json!({
+        "success": false,
+        "message": message,
+        "status": status.as_u16(),
+    });
+    if let Some(request_id) = request_id {
+        body["request_id"] = request_id;
+    }
+    body
+}
+
+/// Connects to the broker at `host:port` and bridges until the process exits. The connection
+/// is re-established after errors.
+pub async fn run(state: AppState, host: String, port: u16) {
+    let client_id = format!("tcp_communication-{:08x}", rand::random::<u32>());
+    let mut options = MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(Duration::from_secs(30));
+    let (client, mut eventloop) = AsyncClient::new(options, crate::config::MQTT_QUEUE_CAPACITY);
+
+    tokio::spawn(publish_events(state.clone(), client.clone()));
+
+    let commands = format!("{}/+/cmd/+", TOPIC_PREFIX);
+    loop {
+        match eventloop.poll().await {
+            // Subscriptions do not survive a reconnect with a clean session
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                println!("Connected to MQTT broker");
+                if let Err(err) = client.subscribe(&commands, QoS::AtLeastOnce).await {
+                    eprintln!("Failed to subscribe to {}: {}", commands, err);
+                }
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                let Some((imei, action)) = parse_command_topic(&publish.topic) else {
+                    continue;
+                };
+                let (imei, action) = (imei.to_string(), action.to_string());
+                let (state, client) = (state.clone(), client.clone());
+                // Commands wait on the scooter, so each runs on its own task
+                tokio::spawn(async move {
+                    let body = run_command(&state, &imei, &action, &publish.payload).await;
+                    let topic = reply_topic(&imei, &action);
+                    if let Err(err) = client
+                        .publish(topic, QoS::AtLeastOnce, false, body.to_string())
+                        .await
+                    {
+                        eprintln!("Failed to publish MQTT reply: {}", err);
+                    }
+                });
+            }
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("MQTT connection error: {}", err);
+                tokio::time::sleep(crate::config::MQTT_RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+async fn publish_events(state: AppState, client: AsyncClient) {
+    let mut events = state.events.subscribe();
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                eprintln!("MQTT bridge fell behind, {} events were dropped", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        let Some(topic) = event_topic(&event) else {
+            continue;
+        };
+        let Ok(payload) = serde_json::to_string(&event) else {
+            continue;
+        };
+        if let Err(err) = client
+            .publish(topic, QoS::AtLeastOnce, false, payload)
+            .await
+        {
+            eprintln!("Failed to publish event to MQTT: {}", err);
+        }
+    }
+}
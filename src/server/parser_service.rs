@@ -0,0 +1,85 @@
+use serde::Serialize;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::commands::{parser::parse_command, scooter_command::ScooterCommand};
+
+use super::codec::{read_frame, FrameDecoder};
+
+/// One line of output from the parser port.
+#[derive(Debug, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum DecodeResult {
+    Ok {
+        frame: String,
+        command: ScooterCommand,
+    },
+    Error {
+        frame: Option<String>,
+        /// Machine-readable failure, e.g. `unknown_command`. See [`crate::errors::ParseError::kind`].
+        kind: &'static str,
+        error: String,
+    },
+}
+
+pub fn decode(frame: &str) -> DecodeResult {
+    match parse_command(frame) {
+        Ok(command) => DecodeResult::Ok {
+            frame: frame.to_string(),
+            command,
+        },
+        Err(error) => DecodeResult::Error {
+            frame: Some(frame.to_string()),
+            kind: error.kind(),
+            error: error.to_string(),
+        },
+    }
+}
+
+pub async fn start_parser_server(address: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    println!("Parser server listening on {}", address);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                println!("New parser connection from {}", addr);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_parser_connection(stream).await {
+                        eprintln!("Error handling parser connection: {}", e);
+                    }
+                });
+            }
+            Err(e) => {
+                eprintln!("Error accepting parser connection: {}", e);
+            }
+        }
+    }
+}
+
+/// Decodes raw device frames from `stream` and writes one JSON object per line back to it.
+pub async fn handle_parser_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+) -> std::io::Result<()> {
+    let mut decoder = FrameDecoder::default();
+
+    loop {
+        let result = match read_frame(&mut stream, &mut decoder).await {
+            Ok(Some(frame)) => decode(&frame),
+            Ok(None) => return Ok(()), // Connection closed
+            // The decoder has already discarded the oversized frame, so keep going.
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => DecodeResult::Error {
+                frame: None,
+                kind: "frame_too_large",
+                error: e.to_string(),
+            },
+            Err(e) => return Err(e),
+        };
+
+        let mut line = serde_json::to_string(&result).map_err(std::io::Error::other)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes()).await?;
+    }
+}
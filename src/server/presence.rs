@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    events::{self, DeviceEvent},
+    AppState,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Connectivity {
+    /// Heard from within one heartbeat interval plus the grace period.
+    Online,
+    /// Missed at least one heartbeat but not yet enough to be considered offline.
+    Degraded,
+    /// Missed too many heartbeats, or the connection is closed.
+    Offline,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HeartbeatPolicy {
+    pub interval: Duration,
+    pub grace: Duration,
+    pub missed_before_offline: u32,
+}
+
+impl HeartbeatPolicy {
+    pub fn connectivity(&self, elapsed: Duration) -> Connectivity {
+        if elapsed <= self.interval + self.grace {
+            Connectivity::Online
+        } else if elapsed <= self.interval * self.missed_before_offline + self.grace {
+            Connectivity::Degraded
+        } else {
+            Connectivity::Offline
+        }
+    }
+}
+
+impl Default for HeartbeatPolicy {
+    fn default() -> Self {
+        Self {
+            interval: crate::config::heartbeat_interval(),
+            grace: crate::config::heartbeat_grace(),
+            missed_before_offline: crate::config::HEARTBEAT_MISSED_BEFORE_OFFLINE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Presence {
+    pub last_seen: DateTime<Utc>,
+    pub connected: bool,
+    pub connectivity: Connectivity,
+}
+
+/// Last-seen time and derived connectivity per IMEI. Entries outlive the connection so
+/// offline scooters can still be queried.
+pub type PresenceMap = Arc<Mutex<HashMap<String, Presence>>>;
+
+/// A change of connectivity, as `(from, to)`.
+pub type Transition = (Connectivity, Connectivity);
+
+/// Records a frame from `imei`. Returns the transition if the scooter was not online.
+pub fn record_seen(presence: &PresenceMap, imei: &str, now: DateTime<Utc>) -> Option<Transition> {
+    let mut presence = presence.lock().unwrap();
+    let previous = presence.insert(
+        imei.to_string(),
+        Presence {
+            last_seen: now,
+            connected: true,
+            connectivity: Connectivity::Online,
+        },
+    );
+
+    match previous {
+        Some(previous) if previous.connectivity == Connectivity::Online => None,
+        Some(previous) => Some((previous.connectivity, Connectivity::Online)),
+        None => None,
+    }
+}
+
+/// Marks `imei` as offline because its connection ended.
+pub fn record_disconnected(presence: &PresenceMap, imei: &str) -> Option<Transition> {
+    let mut presence = presence.lock().unwrap();
+    let entry = presence.get_mut(imei)?;
+    entry.connected = false;
+
+    let previous = std::mem::replace(&mut entry.connectivity, Connectivity::Offline);
+    (previous != Connectivity::Offline).then_some((previous, Connectivity::Offline))
+}
+
+/// Whether `imei` has sent a frame since startup.
+pub fn is_known(presence: &PresenceMap, imei: &str) -> bool {
+    presence.lock().unwrap().contains_key(imei)
+}
+
+/// Re-derives every scooter's connectivity from its last-seen time.
+pub fn refresh(
+    presence: &PresenceMap,
+    policy: &HeartbeatPolicy,
+    now: DateTime<Utc>,
+) -> Vec<(String, Transition)> {
+    let mut presence = presence.lock().unwrap();
+    let mut transitions = Vec::new();
+
+    for (imei, entry) in presence.iter_mut() {
+        let connectivity = if entry.connected {
+            let elapsed = (now - entry.last_seen).to_std().unwrap_or_default();
+            policy.connectivity(elapsed)
+        } else {
+            Connectivity::Offline
+        };
+
+        if connectivity != entry.connectivity {
+            transitions.push((imei.clone(), (entry.connectivity, connectivity)));
+            entry.connectivity = connectivity;
+        }
+    }
+
+    transitions
+}
+
+pub fn publish_transition(state: &AppState, imei: &str, (from, to): Transition) {
+    println!("Scooter {} went from {:?} to {:?}", imei, from, to);
+    events::publish(
+        &state.events,
+        DeviceEvent::ConnectivityChanged {
+            imei: imei.to_string(),
+            from,
+            to,
+        },
+    );
+}
+
+/// Periodically re-derives connectivity and publishes a transition event whenever a scooter
+/// misses heartbeats.
+pub async fn monitor(state: AppState) {
+    let mut interval = tokio::time::interval(crate::config::PRESENCE_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        for (imei, transition) in refresh(&state.presence, &state.heartbeat_policy, Utc::now()) {
+            publish_transition(&state, &imei, transition);
+        }
+    }
+}
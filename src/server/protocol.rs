@@ -1,114 +1,107 @@
-use regex::Regex;
+//! Matching of scooter replies against the request they answer, on top of `parse_command`.
 
-use super::commands::R0Operation;
+use crate::commands::{
+    parser::parse_command, positioning_command::Status, scooter_command::ScooterCommand,
+    unlock_command::R0Operation,
+};
+use crate::errors::ParseError;
 
+use super::registry::ScooterSettings;
+
+/// Returns the key from `response` if it is the R0 reply to `r0_operation` sent by `user_id`
+/// at `timestamp`.
 pub fn validate_r0_response(
     response: &str,
     imei: &str,
     r0_operation: &R0Operation,
     user_id: u32,
     timestamp: i64,
-) -> Result<String, &'static str> {
-    match validate_command(
-        response,
-        imei,
-        "R0",
-        &[
-            r0_operation
-                .try_into()
-                .map_err(|_| "Invalid R0 operation")?,
-            &r"\d+",
-            &user_id.to_string(),
-            &timestamp.to_string(),
-        ],
-    ) {
-        Ok(_) => {
-            let key = response
-                .split(',')
-                .nth(5)
-                .ok_or("Failed to extract key from response")?;
+) -> Result<String, ParseError> {
+    match parse_command(response)? {
+        ScooterCommand::UnlockOrLockResponse {
+            imei: reply_imei,
+            operation,
+            key,
+            user_id: reply_user_id,
+            timestamp: reply_timestamp,
+        } if reply_imei == imei
+            && operation == *r0_operation
+            && reply_user_id == user_id.to_string()
+            && reply_timestamp.timestamp() == timestamp =>
+        {
             Ok(key.to_string())
         }
-        Err(err) => Err(err),
+        _ => Err(unexpected("R0", response)),
     }
 }
 
+/// Checks that `response` confirms the L0 unlock sent by `user_id` at `timestamp`.
 pub fn validate_l0_response(
     response: &str,
     imei: &str,
     user_id: u32,
     timestamp: i64,
-) -> Result<(), &'static str> {
-    validate_command(
-        response,
-        imei,
-        "L0",
-        &[&"0", &user_id.to_string(), &timestamp.to_string()],
-    )
+) -> Result<(), ParseError> {
+    match parse_command(response)? {
+        ScooterCommand::UnlockResponse {
+            imei: reply_imei,
+            status: Status::Success,
+            user_id: reply_user_id,
+            timestamp: reply_timestamp,
+        } if reply_imei == imei
+            && reply_user_id == user_id.to_string()
+            && reply_timestamp.timestamp() == timestamp =>
+        {
+            Ok(())
+        }
+        _ => Err(unexpected("L0", response)),
+    }
 }
 
-pub fn validate_l1_response(response: &str, imei: &str, user_id: u32) -> Result<(), &'static str> {
-    validate_command(
-        response,
-        imei,
-        "L1",
-        &[
-            &"0", // Expect "0" (success)
-            &user_id.to_string(),
-            r"\d+", // Unlock timestamp
-            r"\d+", // Cycling time
-        ],
-    )
+/// Checks that `response` confirms an L1 lock sent by `user_id`.
+pub fn validate_l1_response(response: &str, imei: &str, user_id: u32) -> Result<(), ParseError> {
+    match parse_command(response)? {
+        ScooterCommand::LockResponse {
+            imei: reply_imei,
+            status: Status::Success,
+            user_id: reply_user_id,
+            ..
+        } if reply_imei == imei && reply_user_id == user_id.to_string() => Ok(()),
+        _ => Err(unexpected("L1", response)),
+    }
 }
 
+/// Checks that `response` is the S7 echo of `settings`.
 pub fn validate_s7_response(
     response: &str,
     imei: &str,
-    headlight_switch: u8,
-    mode_setting: u8,
-    throttle_response: u8,
-    taillights_flashing: u8,
-) -> Result<(), &'static str> {
-    validate_command(
-        response,
-        imei,
-        "S7",
-        &[
-            &headlight_switch.to_string(),
-            &mode_setting.to_string(),
-            &throttle_response.to_string(),
-            &taillights_flashing.to_string(),
-        ],
-    )
+    settings: &ScooterSettings,
+) -> Result<(), ParseError> {
+    match parse_command(response)? {
+        ScooterCommand::ScooterSetting {
+            imei: reply_imei,
+            headlight_switch,
+            mode_setting,
+            throttle_response,
+            taillights_flashing,
+        } if reply_imei == imei
+            && ScooterSettings::reported(
+                &headlight_switch,
+                &mode_setting,
+                &throttle_response,
+                &taillights_flashing,
+            ) == *settings =>
+        {
+            Ok(())
+        }
+        _ => Err(unexpected("S7", response)),
+    }
 }
 
-pub fn validate_command(
-    response: &str,
-    imei: &str,
-    command_type: &str,
-    content: &[&str],
-) -> Result<(), &'static str> {
-    let vendor = crate::config::VENDOR;
-
-    let content_regex = content.join(",");
-
-    let pattern = format!(
-        r"^\*SCOR,{vendor},{imei},{command_type},{content}#\n",
-        vendor = vendor,
-        imei = imei,
-        command_type = command_type,
-        content = content_regex
-    );
-    let regex = Regex::new(&pattern).map_err(|_| "Failed to compile regex")?;
-
-    if regex.is_match(response) {
-        Ok(())
-    } else {
-        let error_message = format!(
-            "Invalid response format: expected pattern '{}', got '{}'",
-            pattern, response
-        );
-        Err(Box::leak(error_message.into_boxed_str()))
+fn unexpected(command: &str, response: &str) -> ParseError {
+    ParseError::UnexpectedContent {
+        command: command.to_string(),
+        frame: response.to_string(),
     }
 }
 
@@ -0,0 +1,258 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::alarms::Alarm;
+use crate::commands::{
+    hearbeat_command::{ChargingStatus, ScooterStatus},
+    positioning_command::{Mode, PositioningResponse, PositioningStatus, Status},
+    scooter_command::ScooterCommand,
+    scooter_setting_command::{HeadlightSwitch, ModeSetting, TaillightsFlashing, ThrottleResponse},
+};
+
+/// A position fix reported in a D0 frame.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Position {
+    /// WGS84 decimal degrees.
+    pub latitude: f64,
+    /// WGS84 decimal degrees.
+    pub longitude: f64,
+    /// Metres above sea level.
+    pub altitude: f32,
+    pub satellites_number: u8,
+    pub positioning_accuracy: f32,
+    pub mode: Mode,
+    pub fixed_at: DateTime<Utc>,
+}
+
+/// S7 settings as last confirmed by the scooter. `None` until a setting has been reported.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScooterSettings {
+    pub headlight_switch: Option<HeadlightSwitch>,
+    pub mode_setting: Option<ModeSetting>,
+    pub throttle_response: Option<ThrottleResponse>,
+    pub taillights_flashing: Option<TaillightsFlashing>,
+}
+
+impl ScooterSettings {
+    /// The settings carried by an S7 frame, leaving "don't set" values as `None`.
+    pub fn reported(
+        headlight_switch: &HeadlightSwitch,
+        mode_setting: &ModeSetting,
+        throttle_response: &ThrottleResponse,
+        taillights_flashing: &TaillightsFlashing,
+    ) -> Self {
+        Self {
+            headlight_switch: Some(headlight_switch.clone())
+                .filter(|value| *value != HeadlightSwitch::NoSet),
+            mode_setting: Some(mode_setting.clone()).filter(|value| *value != ModeSetting::NoSet),
+            throttle_response: Some(throttle_response.clone())
+                .filter(|value| *value != ThrottleResponse::NoSet),
+            taillights_flashing: Some(taillights_flashing.clone())
+                .filter(|value| *value != TaillightsFlashing::NoSet),
+        }
+    }
+
+    /// Overwrites the settings that `other` sets.
+    pub fn merge(&mut self, other: ScooterSettings) {
+        if other.headlight_switch.is_some() {
+            self.headlight_switch = other.headlight_switch;
+        }
+        if other.mode_setting.is_some() {
+            self.mode_setting = other.mode_setting;
+        }
+        if other.throttle_response.is_some() {
+            self.throttle_response = other.throttle_response;
+        }
+        if other.taillights_flashing.is_some() {
+            self.taillights_flashing = other.taillights_flashing;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ScooterSettings::default()
+    }
+}
+
+/// Everything known about a scooter from the frames it has sent.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceState {
+    /// Battery level in percent.
+    pub power: Option<u8>,
+    /// Battery voltage in volts.
+    pub voltage: Option<f32>,
+    /// GSM signal strength, 0 to 31.
+    pub signal: Option<u8>,
+    pub status: Option<ScooterStatus>,
+    pub charging: Option<ChargingStatus>,
+    pub position: Option<Position>,
+    /// Speed in km/h from the last S6 report.
+    pub speed: Option<u16>,
+    /// Distance of the current ride in metres from the last S6 report.
+    pub ride_mileage: Option<u32>,
+    pub settings: ScooterSettings,
+    /// Seconds between D1 tracking uploads, as last confirmed by the scooter.
+    pub tracking_interval: Option<u16>,
+    /// Most recent W0 alarms, oldest first.
+    pub alarms: Vec<Alarm>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DeviceState {
+    pub(crate) fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            power: None,
+            voltage: None,
+            signal: None,
+            status: None,
+            charging: None,
+            position: None,
+            speed: None,
+            ride_mileage: None,
+            settings: ScooterSettings::default(),
+            tracking_interval: None,
+            alarms: Vec::new(),
+            updated_at: now,
+        }
+    }
+}
+
+/// Last-known state per IMEI. Like presence, entries outlive the connection.
+pub type DeviceRegistry = Arc<Mutex<HashMap<String, DeviceState>>>;
+
+/// Folds a decoded frame into the scooter's state. Frames that carry no state are ignored.
+///
+/// Returns the new lock status if the frame changed it.
+pub fn record(
+    registry: &DeviceRegistry,
+    imei: &str,
+    command: &ScooterCommand,
+    now: DateTime<Utc>,
+) -> Option<ScooterStatus> {
+    if !carries_state(command) {
+        return None;
+    }
+
+    let mut registry = registry.lock().unwrap();
+    let state = registry
+        .entry(imei.to_string())
+        .or_insert_with(|| DeviceState::new(now));
+    state.updated_at = now;
+    let previous_status = state.status.clone();
+
+    match command {
+        ScooterCommand::SigningIn {
+            voltage,
+            power,
+            signal,
+            ..
+        } => {
+            state.voltage = Some(*voltage);
+            state.power = Some(*power);
+            state.signal = Some(*signal);
+            // S7 settings are back to the firmware defaults after a restart
+            state.settings = ScooterSettings::default();
+        }
+        ScooterCommand::HeartBeat {
+            status,
+            voltage,
+            signal,
+            power,
+            charging,
+            ..
+        } => {
+            state.status = Some(status.clone());
+            state.voltage = Some(*voltage);
+            state.signal = Some(*signal);
+            state.power = Some(*power);
+            state.charging = Some(charging.clone());
+        }
+        ScooterCommand::PositioningResponse(response) => {
+            state.position = Some(position(response));
+        }
+        ScooterCommand::StatusResponse(report) => {
+            state.power = Some(report.power);
+            state.speed = Some(report.speed);
+            state.charging = Some(report.charging.clone());
+            state.status = Some(report.status.clone());
+            state.signal = Some(report.signal);
+            if report.ride_mileage.is_some() {
+                state.ride_mileage = report.ride_mileage;
+            }
+            if report.mode_setting != ModeSetting::NoSet {
+                state.settings.mode_setting = Some(report.mode_setting.clone());
+            }
+        }
+        ScooterCommand::UnlockResponse {
+            status: Status::Success,
+            ..
+        } => {
+            state.status = Some(ScooterStatus::Unlocked);
+            // Unlocking also restores the default S7 settings
+            state.settings = ScooterSettings::default();
+        }
+        ScooterCommand::LockResponse {
+            status: Status::Success,
+            ..
+        } => state.status = Some(ScooterStatus::Locked),
+        ScooterCommand::ScooterSetting {
+            headlight_switch,
+            mode_setting,
+            throttle_response,
+            taillights_flashing,
+            ..
+        } => {
+            // "Don't set" echoes leave the previous value in place
+            state.settings.merge(ScooterSettings::reported(
+                headlight_switch,
+                mode_setting,
+                throttle_response,
+                taillights_flashing,
+            ));
+        }
+        ScooterCommand::TrackingIntervalResponse { interval, .. } => {
+            state.tracking_interval = Some(*interval);
+        }
+        _ => {}
+    }
+
+    (state.status != previous_status)
+        .then(|| state.status.clone())
+        .flatten()
+}
+
+pub fn get(registry: &DeviceRegistry, imei: &str) -> Option<DeviceState> {
+    registry.lock().unwrap().get(imei).cloned()
+}
+
+fn carries_state(command: &ScooterCommand) -> bool {
+    match command {
+        ScooterCommand::SigningIn { .. }
+        | ScooterCommand::HeartBeat { .. }
+        | ScooterCommand::ScooterSetting { .. }
+        | ScooterCommand::StatusResponse(_)
+        | ScooterCommand::TrackingIntervalResponse { .. } => true,
+        ScooterCommand::PositioningResponse(response) => {
+            matches!(response.positioning_status, PositioningStatus::Effective)
+        }
+        ScooterCommand::UnlockResponse { status, .. }
+        | ScooterCommand::LockResponse { status, .. } => matches!(status, Status::Success),
+        _ => false,
+    }
+}
+
+pub fn position(response: &PositioningResponse) -> Position {
+    Position {
+        latitude: response.latitude,
+        longitude: response.longitude,
+        altitude: response.altitude,
+        satellites_number: response.satellites_number,
+        positioning_accuracy: response.positioning_accuracy,
+        mode: response.mode.clone(),
+        fixed_at: response.utc_datetime,
+    }
+}
@@ -0,0 +1,110 @@
+use crate::server::{
+    command_enums::{SpeedMode, Turn},
+    handler::*,
+    registry::{self, ScooterSettings},
+    AppState,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+/// Switches take `on` or `off`, the speed mode `low`, `medium` or `high`.
+/// Omitted fields are left unchanged on the scooter.
+#[derive(Deserialize)]
+pub struct SettingsRequest {
+    #[serde(default)]
+    pub headlight_switch: Turn,
+    #[serde(default)]
+    pub speed_mode: SpeedMode,
+    #[serde(default)]
+    pub throttle_response: Turn,
+    #[serde(default)]
+    pub taillights_flashing: Turn,
+}
+
+impl SettingsRequest {
+    fn is_empty(&self) -> bool {
+        matches!(self.headlight_switch, Turn::DontSet)
+            && matches!(self.speed_mode, SpeedMode::DontSet)
+            && matches!(self.throttle_response, Turn::DontSet)
+            && matches!(self.taillights_flashing, Turn::DontSet)
+    }
+}
+
+#[derive(Serialize)]
+pub struct SettingsResponse {
+    pub success: bool,
+    pub message: String,
+    pub imei: String,
+    /// The scooter's settings as last confirmed, including fields not in this request.
+    pub settings: Option<ScooterSettings>,
+}
+
+pub async fn settings_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+    Json(payload): Json<SettingsRequest>,
+) -> impl IntoResponse {
+    if payload.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(SettingsResponse {
+                success: false,
+                message: "No settings to change".to_string(),
+                imei,
+                settings: None,
+            }),
+        );
+    }
+
+    let connection = match get_client(&state.clients, &imei).await {
+        Ok(connection) => connection,
+        Err(err) => {
+            return (
+                err.status_code(),
+                Json(SettingsResponse {
+                    success: false,
+                    message: err.to_string(),
+                    imei,
+                    settings: None,
+                }),
+            );
+        }
+    };
+
+    if let Err(err) = request_s7(
+        &connection,
+        &imei,
+        &payload.headlight_switch,
+        &payload.speed_mode,
+        &payload.throttle_response,
+        &payload.taillights_flashing,
+    )
+    .await
+    {
+        return (
+            err.status_code(),
+            Json(SettingsResponse {
+                success: false,
+                message: err.to_string(),
+                imei,
+                settings: None,
+            }),
+        );
+    }
+
+    // The echo has been folded into the registry before it was routed to us
+    let settings = registry::get(&state.registry, &imei).map(|device| device.settings);
+    (
+        StatusCode::OK,
+        Json(SettingsResponse {
+            success: true,
+            message: "Settings changed".to_string(),
+            imei,
+            settings,
+        }),
+    )
+}
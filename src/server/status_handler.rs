@@ -0,0 +1,68 @@
+use crate::{
+    commands::status_command::StatusReport,
+    server::ClientMap,
+    server::{commands, handler::*},
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct StatusRequest {
+    pub imei: String,
+}
+
+#[derive(Serialize)]
+pub struct StatusResponse {
+    pub success: bool,
+    pub message: String,
+    pub imei: String,
+    pub report: Option<StatusReport>,
+}
+
+pub async fn status_handler(
+    State(clients): State<ClientMap>,
+    Json(payload): Json<StatusRequest>,
+) -> impl IntoResponse {
+    let imei = payload.imei.clone();
+
+    let connection = match get_client(&clients, &imei).await {
+        Ok(connection) => connection,
+        Err(err) => {
+            return (
+                err.status_code(),
+                Json(StatusResponse {
+                    success: false,
+                    message: err.to_string(),
+                    imei,
+                    report: None,
+                }),
+            );
+        }
+    };
+
+    let s6_command = commands::generate_s6_command(&imei);
+    match request_s6(&connection, &s6_command, &imei).await {
+        Ok(report) => (
+            StatusCode::OK,
+            Json(StatusResponse {
+                success: true,
+                message: "Status report received".to_string(),
+                imei,
+                report: Some(report),
+            }),
+        ),
+        Err(err) => (
+            err.status_code(),
+            Json(StatusResponse {
+                success: false,
+                message: err.to_string(),
+                imei,
+                report: None,
+            }),
+        ),
+    }
+}
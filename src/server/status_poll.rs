@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use tokio::task::JoinSet;
+
+use crate::commands::hearbeat_command::ScooterStatus;
+
+use super::{commands, handler::send_command, tracking, AppState};
+
+/// Sends an S6 query to every connected scooter that is being ridden, once per `interval`.
+///
+/// Replies are not awaited; they update the registry through each connection's reader.
+pub async fn run(state: AppState, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+        poll(&state).await;
+    }
+}
+
+/// Sends one round of S6 queries, to all scooters at once so that one slow connection does not
+/// hold up the others, and returns the IMEIs that were polled.
+pub async fn poll(state: &AppState) -> Vec<String> {
+    let connections: Vec<_> = state
+        .clients
+        .lock()
+        .await
+        .iter()
+        .map(|(imei, connection)| (imei.clone(), connection.clone()))
+        .collect();
+
+    let mut sends = JoinSet::new();
+    for (imei, connection) in connections {
+        // Parked scooters report through their heartbeat often enough
+        if tracking::current_status(state, &imei) != ScooterStatus::Unlocked {
+            continue;
+        }
+
+        sends.spawn(async move {
+            let command = commands::generate_s6_command(&imei);
+            let result = send_command(&connection, &command).await;
+            (imei, result)
+        });
+    }
+
+    let mut polled = Vec::new();
+    while let Some(sent) = sends.join_next().await {
+        match sent {
+            Ok((imei, Ok(()))) => polled.push(imei),
+            Ok((imei, Err(err))) => println!("Failed to poll status of {}: {}", imei, err),
+            Err(err) => println!("Status poll task failed: {}", err),
+        }
+    }
+    polled.sort();
+    polled
+}
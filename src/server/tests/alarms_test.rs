@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod alarms_tests {
+    use std::time::Duration;
+
+    use chrono::{TimeDelta, Utc};
+    use tokio::{io::AsyncWriteExt, time::timeout};
+
+    use crate::{
+        commands::alarm_command::AlarmType,
+        config,
+        server::{
+            alarms::{self, Severity},
+            events::DeviceEvent,
+            registry::{self, DeviceRegistry},
+            tests::support::{read_command, sign_in, IMEI},
+            AppState,
+        },
+    };
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_theft_and_tamper_alarms_are_critical() {
+        assert_eq!(
+            Severity::from(&AlarmType::IllegalRemoval),
+            Severity::Critical
+        );
+        assert_eq!(
+            Severity::from(&AlarmType::IllegalDemolition),
+            Severity::Critical
+        );
+        assert_eq!(Severity::from(&AlarmType::LiftedUp), Severity::Warning);
+        assert_eq!(Severity::from(&AlarmType::LowPower), Severity::Info);
+    }
+
+    #[test]
+    fn test_repeats_within_window_are_folded() {
+        let registry = DeviceRegistry::default();
+        let start = Utc::now();
+
+        let first = alarms::record(&registry, IMEI, &AlarmType::LiftedUp, WINDOW, start);
+        assert!(first.is_some());
+        let later = start + TimeDelta::seconds(30);
+        assert!(alarms::record(&registry, IMEI, &AlarmType::LiftedUp, WINDOW, later).is_none());
+        // Other alarm types are not affected
+        assert!(alarms::record(&registry, IMEI, &AlarmType::Falling, WINDOW, later).is_some());
+
+        let stored = registry::get(&registry, IMEI).unwrap().alarms;
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].repeats, 1);
+        assert_eq!(stored[0].last_seen_at, later);
+
+        let after_window = start + TimeDelta::seconds(61);
+        let raised =
+            alarms::record(&registry, IMEI, &AlarmType::LiftedUp, WINDOW, after_window).unwrap();
+        assert_eq!(raised.repeats, 0);
+        assert_eq!(registry::get(&registry, IMEI).unwrap().alarms.len(), 3);
+    }
+
+    #[test]
+    fn test_alarm_history_is_bounded() {
+        let registry = DeviceRegistry::default();
+        let start = Utc::now();
+
+        for minute in 0..config::ALARM_HISTORY_SIZE as i64 + 5 {
+            let now = start + TimeDelta::minutes(minute * 2);
+            alarms::record(&registry, IMEI, &AlarmType::LowPower, WINDOW, now);
+        }
+
+        let stored = registry::get(&registry, IMEI).unwrap().alarms;
+        assert_eq!(stored.len(), config::ALARM_HISTORY_SIZE);
+        assert_eq!(stored[0].raised_at, start + TimeDelta::minutes(10));
+    }
+
+    #[tokio::test]
+    async fn test_alarm_frame_is_published_once() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        let mut events = state.events.subscribe();
+
+        for _ in 0..2 {
+            device
+                .write_all(format!("*SCOR,LZ,{IMEI},W0,3#\n").as_bytes())
+                .await
+                .unwrap();
+            read_command(&mut device).await;
+        }
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},H0,1,412,28,80,0#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let mut alarms = Vec::new();
+        loop {
+            match timeout(Duration::from_secs(1), events.recv())
+                .await
+                .unwrap()
+                .unwrap()
+            {
+                DeviceEvent::Alarm { imei, alarm } => {
+                    assert_eq!(imei, IMEI);
+                    alarms.push(alarm);
+                }
+                DeviceEvent::Frame { command, .. } if command.code() == "H0" => break,
+                _ => {}
+            }
+        }
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].alarm_type, AlarmType::IllegalRemoval);
+        assert_eq!(alarms[0].severity, Severity::Critical);
+
+        let stored = registry::get(&state.registry, IMEI).unwrap().alarms;
+        assert_eq!(stored[0].repeats, 1);
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod beep_tests {
+    use std::time::Duration;
+
+    use axum::{extract::State, http::StatusCode, Json};
+    use serde_json::Value;
+    use tokio::{io::AsyncWriteExt, time::timeout};
+
+    use crate::{
+        commands::beep_command::BeepPlayContent,
+        server::{
+            beep_handler::{beep_handler, BeepRequest},
+            tests::support::{body, read_command, sign_in, IMEI},
+            AppState,
+        },
+    };
+
+    async fn beep(
+        state: &AppState,
+        play_content: BeepPlayContent,
+    ) -> tokio::task::JoinHandle<(StatusCode, Value)> {
+        let clients = state.clients.clone();
+        tokio::spawn(async move {
+            let request = Json(BeepRequest {
+                imei: IMEI.to_string(),
+                play_content,
+            });
+            body(beep_handler(State(clients), request).await).await
+        })
+    }
+
+    #[tokio::test]
+    async fn test_beep_waits_for_matching_echo() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        let request = beep(&state, BeepPlayContent::FindScooterAlert).await;
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},V0,2#\n")
+        );
+        // An echo of a different play content does not confirm the request
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},V0,1#\n").as_bytes())
+            .await
+            .unwrap();
+        assert!(!request.is_finished());
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},V0,2#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+    }
+
+    #[tokio::test]
+    async fn test_beep_unknown_scooter() {
+        let state = AppState::new();
+
+        let (status, body) = beep(&state, BeepPlayContent::TurnOnVoice)
+            .await
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["success"], false);
+    }
+
+    #[test]
+    fn test_play_content_is_requested_by_name() {
+        let request: BeepRequest =
+            serde_json::from_str(r#"{"imei":"123456789123456","play_content":"turn_off_voice"}"#)
+                .unwrap();
+        assert_eq!(request.play_content, BeepPlayContent::TurnOffVoice);
+    }
+}
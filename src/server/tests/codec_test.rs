@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod frame_decoder_tests {
+    use crate::server::codec::{FrameDecoder, FrameError};
+
+    const HEARTBEAT: &str = "*SCOR,LZ,123456789123456,H0,1,412,28,80,0#\n";
+
+    #[test]
+    fn test_next_frame_with_single_frame() {
+        let mut decoder = FrameDecoder::new(1024);
+        decoder.push(HEARTBEAT.as_bytes());
+
+        assert_eq!(decoder.next_frame(), Some(Ok(HEARTBEAT.to_string())));
+        assert_eq!(decoder.next_frame(), None);
+    }
+
+    #[test]
+    fn test_next_frame_with_frame_split_across_reads() {
+        let mut decoder = FrameDecoder::new(1024);
+        let (first, second) = HEARTBEAT.split_at(20);
+
+        decoder.push(first.as_bytes());
+        assert_eq!(decoder.next_frame(), None);
+
+        decoder.push(second.as_bytes());
+        assert_eq!(decoder.next_frame(), Some(Ok(HEARTBEAT.to_string())));
+    }
+
+    #[test]
+    fn test_next_frame_with_terminator_split_across_reads() {
+        let mut decoder = FrameDecoder::new(1024);
+        let (first, second) = HEARTBEAT.split_at(HEARTBEAT.len() - 1);
+
+        decoder.push(first.as_bytes());
+        assert_eq!(decoder.next_frame(), None);
+
+        decoder.push(second.as_bytes());
+        assert_eq!(decoder.next_frame(), Some(Ok(HEARTBEAT.to_string())));
+    }
+
+    #[test]
+    fn test_next_frame_with_coalesced_frames() {
+        let mut decoder = FrameDecoder::new(1024);
+        let sign_in = "*SCOR,LZ,123456789123456,Q0,412,80,28#\n";
+        decoder.push(format!("{}{}", sign_in, HEARTBEAT).as_bytes());
+
+        assert_eq!(decoder.next_frame(), Some(Ok(sign_in.to_string())));
+        assert_eq!(decoder.next_frame(), Some(Ok(HEARTBEAT.to_string())));
+        assert_eq!(decoder.next_frame(), None);
+    }
+
+    #[test]
+    fn test_next_frame_strips_text_reserved_header() {
+        let mut decoder = FrameDecoder::new(1024);
+        decoder.push(format!("0xFFFF{}", HEARTBEAT).as_bytes());
+
+        assert_eq!(decoder.next_frame(), Some(Ok(HEARTBEAT.to_string())));
+    }
+
+    #[test]
+    fn test_next_frame_strips_binary_reserved_header() {
+        let mut decoder = FrameDecoder::new(1024);
+        decoder.push(&[0xFF, 0xFF]);
+        decoder.push(HEARTBEAT.as_bytes());
+
+        assert_eq!(decoder.next_frame(), Some(Ok(HEARTBEAT.to_string())));
+    }
+
+    #[test]
+    fn test_next_frame_with_oversized_unterminated_data() {
+        let mut decoder = FrameDecoder::new(16);
+        decoder.push(&[b'A'; 17]);
+
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Err(FrameError::TooLarge { size: 17, max: 16 }))
+        );
+
+        // The decoder resynchronises on the next frame
+        decoder.push(b"*SCOR,LZ,1,H0#\n");
+        assert_eq!(
+            decoder.next_frame(),
+            Some(Ok("*SCOR,LZ,1,H0#\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_next_frame_with_oversized_terminated_frame() {
+        let mut decoder = FrameDecoder::new(16);
+        decoder.push(HEARTBEAT.as_bytes());
+
+        assert!(matches!(
+            decoder.next_frame(),
+            Some(Err(FrameError::TooLarge { .. }))
+        ));
+        assert_eq!(decoder.next_frame(), None);
+    }
+}
+
+#[cfg(test)]
+mod read_frame_tests {
+    use tokio::io::AsyncWriteExt;
+
+    use crate::server::codec::{read_frame, FrameDecoder};
+
+    #[tokio::test]
+    async fn test_read_frame_returns_frames_then_eof() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        client
+            .write_all(b"*SCOR,LZ,1,H0#\n*SCOR,LZ,1,W0,1#\n")
+            .await
+            .unwrap();
+        drop(client);
+
+        let mut decoder = FrameDecoder::new(1024);
+        assert_eq!(
+            read_frame(&mut server, &mut decoder).await.unwrap(),
+            Some("*SCOR,LZ,1,H0#\n".to_string())
+        );
+        assert_eq!(
+            read_frame(&mut server, &mut decoder).await.unwrap(),
+            Some("*SCOR,LZ,1,W0,1#\n".to_string())
+        );
+        assert_eq!(read_frame(&mut server, &mut decoder).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_frame_rejects_oversized_frames() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        client.write_all(&[b'A'; 32]).await.unwrap();
+
+        let mut decoder = FrameDecoder::new(16);
+        let err = read_frame(&mut server, &mut decoder).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
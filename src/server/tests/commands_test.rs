@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod generate_r0_command_tests {
-    use crate::server::commands::{self, R0Operation};
+    use crate::{commands::unlock_command::R0Operation, server::commands};
 
     #[test]
     fn test_generate_r0_command_unlock() {
@@ -117,16 +117,26 @@ mod generate_s7_command_tests {
     }
 }
 #[cfg(test)]
-mod generate_command_tests {
-    use crate::server::commands;
+mod generate_tests {
+    use chrono::DateTime;
 
-    #[test]
-    fn test_generate_command_with_simple_content() {
-        let imei = "123456789123456";
-        let command = "R0";
-        let content = &["0", "20", "1234", "1497689816"];
+    use crate::{
+        commands::{
+            beep_command::BeepPlayContent, parser::parse_command, scooter_command::ScooterCommand,
+            unlock_command::R0Operation,
+        },
+        server::commands,
+    };
 
-        let result = commands::generate_command(imei, command, content);
+    #[test]
+    fn test_generate_with_simple_content() {
+        let result = commands::generate(ScooterCommand::UnlockOrLockRequest {
+            imei: "123456789123456".to_string(),
+            operation: R0Operation::Unlock,
+            key_duration: 20,
+            user_id: 1234,
+            timestamp: DateTime::from_timestamp(1497689816, 0).unwrap(),
+        });
 
         assert_eq!(
             result,
@@ -138,12 +148,13 @@ mod generate_command_tests {
     }
 
     #[test]
-    fn test_generate_command_with_multiple_content_items() {
-        let imei = "123456789123456";
-        let command = "L0";
-        let content = &["55", "1234", "1497689816"];
-
-        let result = commands::generate_command(imei, command, content);
+    fn test_generate_with_multiple_content_items() {
+        let result = commands::generate(ScooterCommand::UnlockRequest {
+            imei: "123456789123456".to_string(),
+            key: "55".to_string(),
+            user_id: 1234,
+            timestamp: DateTime::from_timestamp(1497689816, 0).unwrap(),
+        });
 
         assert_eq!(
             result,
@@ -155,12 +166,11 @@ mod generate_command_tests {
     }
 
     #[test]
-    fn test_generate_command_empty_content() {
-        let imei = "123456789123456";
-        let command = "L0";
-        let content: &[&str] = &[];
-
-        let result = commands::generate_command(imei, command, content);
+    fn test_generate_empty_content() {
+        let result = commands::generate(ScooterCommand::Acknowledgement {
+            imei: "123456789123456".to_string(),
+            command: "L0".to_string(),
+        });
 
         assert_eq!(
             result,
@@ -172,43 +182,42 @@ mod generate_command_tests {
     }
 
     #[test]
-    fn test_generate_command_with_special_characters_in_content() {
-        let imei = "123456789123456";
-        let command = "CUSTOM";
-        let content = &["special-content", "1234", "with,comma"];
-
-        let result = commands::generate_command(imei, command, content);
+    fn test_generate_with_enum_content() {
+        let result = commands::generate(ScooterCommand::BeepRequest {
+            imei: "123456789123456".to_string(),
+            play_content: BeepPlayContent::FindScooterAlert,
+        });
 
         assert_eq!(
             result,
             format!(
-                "0xFFFF*SCOS,{vendor},123456789123456,CUSTOM,special-content,1234,with,comma#\n",
+                "0xFFFF*SCOS,{vendor},123456789123456,V0,2#\n",
                 vendor = crate::config::VENDOR
             )
         );
     }
 
     #[test]
-    fn test_generate_command_with_large_content() {
-        let imei = "123456789123456";
-        let command = "DATA";
-        let content = &[
-            "1234567890",
-            "ABCDEFGHIJ",
-            "special&characters",
-            "with=equals",
-            "spaces allowed",
-        ];
+    fn test_generate_with_large_content() {
+        let command = ScooterCommand::UnlockOrLockRequest {
+            imei: "123456789123456".to_string(),
+            operation: R0Operation::RFIDCardLock,
+            key_duration: u8::MAX,
+            user_id: u32::MAX,
+            timestamp: DateTime::from_timestamp(i64::from(u32::MAX), 0).unwrap(),
+        };
 
-        let result = commands::generate_command(imei, command, content);
+        let result = commands::generate(command);
 
         assert_eq!(
             result,
             format!(
-                "0xFFFF*SCOS,{vendor},123456789123456,DATA,1234567890,ABCDEFGHIJ,special&characters,with=equals,spaces allowed#\n",
+                "0xFFFF*SCOS,{vendor},123456789123456,R0,3,255,4294967295,4294967295#\n",
                 vendor = crate::config::VENDOR
             )
         );
+        let decoded = parse_command(&result["0xFFFF".len()..]).unwrap();
+        assert_eq!(commands::generate(decoded), result);
     }
 }
 
@@ -252,3 +261,66 @@ mod generate_l1_ack_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod generate_ack_tests {
+    use crate::server::commands;
+
+    #[test]
+    fn test_generate_ack_for_sign_in() {
+        let result = commands::generate_ack("123456789123456", "Q0");
+
+        assert_eq!(
+            result,
+            Some(format!(
+                "0xFFFF*SCOS,{vendor},123456789123456,Q0#\n",
+                vendor = crate::config::VENDOR
+            ))
+        );
+    }
+
+    #[test]
+    fn test_generate_ack_for_heartbeat() {
+        let result = commands::generate_ack("123456789123456", "H0");
+
+        assert_eq!(
+            result,
+            Some(format!(
+                "0xFFFF*SCOS,{vendor},123456789123456,H0#\n",
+                vendor = crate::config::VENDOR
+            ))
+        );
+    }
+
+    #[test]
+    fn test_generate_ack_for_alarm() {
+        let result = commands::generate_ack("123456789123456", "W0");
+
+        assert_eq!(
+            result,
+            Some(format!(
+                "0xFFFF*SCOS,{vendor},123456789123456,W0#\n",
+                vendor = crate::config::VENDOR
+            ))
+        );
+    }
+
+    #[test]
+    fn test_generate_ack_for_unlock_and_lock() {
+        for code in ["L0", "L1"] {
+            assert_eq!(
+                commands::generate_ack("123456789123456", code),
+                Some(format!(
+                    "0xFFFF*SCOS,{vendor},123456789123456,{code}#\n",
+                    vendor = crate::config::VENDOR
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_ack_for_frame_without_ack() {
+        assert_eq!(commands::generate_ack("123456789123456", "D0"), None);
+        assert_eq!(commands::generate_ack("123456789123456", "S7"), None);
+    }
+}
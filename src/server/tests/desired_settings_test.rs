@@ -0,0 +1,264 @@
+#[cfg(test)]
+mod desired_settings_tests {
+    use std::time::Duration;
+
+    use axum::{
+        extract::{Path, State},
+        http::StatusCode,
+        Json,
+    };
+    use chrono::{TimeDelta, Utc};
+    use serde_json::{json, Value};
+    use tokio::{io::AsyncWriteExt, time::timeout};
+
+    use crate::{
+        commands::scooter_setting_command::{HeadlightSwitch, ModeSetting},
+        server::{
+            desired_settings::{self, drift},
+            desired_settings_handler::{clear_desired_settings_handler, desired_settings_handler},
+            events::DeviceEvent,
+            handler::get_client,
+            history::{CommandOutcome, History},
+            presence,
+            registry::ScooterSettings,
+            tests::support::{body, connect, read_command, read_until, sign_in, IMEI},
+            AppState,
+        },
+    };
+
+    fn low_speed() -> ScooterSettings {
+        ScooterSettings {
+            mode_setting: Some(ModeSetting::LowSpeed),
+            ..ScooterSettings::default()
+        }
+    }
+
+    async fn put_desired(
+        state: &AppState,
+        payload: Value,
+    ) -> tokio::task::JoinHandle<(StatusCode, Value)> {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let request = serde_json::from_value(payload).unwrap();
+            body(
+                desired_settings_handler(State(state), Path(IMEI.to_string()), Json(request)).await,
+            )
+            .await
+        })
+    }
+
+    #[test]
+    fn test_drift_only_counts_reported_settings() {
+        let desired = ScooterSettings {
+            headlight_switch: Some(HeadlightSwitch::Open),
+            ..low_speed()
+        };
+        let reported = ScooterSettings {
+            mode_setting: Some(ModeSetting::HighSpeed),
+            ..ScooterSettings::default()
+        };
+
+        assert_eq!(drift(&desired, &reported), low_speed());
+        assert!(drift(&desired, &desired).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_settings_are_reapplied_after_sign_in_and_unlock() {
+        let mut state = AppState::new();
+        state.settings_policy = low_speed();
+        state.desired_settings.lock().unwrap().insert(
+            IMEI.to_string(),
+            ScooterSettings {
+                headlight_switch: Some(HeadlightSwitch::Open),
+                ..ScooterSettings::default()
+            },
+        );
+        let mut device = connect(&state).await;
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},Q0,412,80,28#\n").as_bytes())
+            .await
+            .unwrap();
+        read_until(&mut device, &format!("*SCOS,LZ,{IMEI},S7,2,1,0,0#\n")).await;
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},L0,0,1,1497689816#\n").as_bytes())
+            .await
+            .unwrap();
+        read_until(&mut device, &format!("*SCOS,LZ,{IMEI},S7,2,1,0,0#\n")).await;
+
+        // A failed unlock leaves the scooter as it was
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},L0,1,1,1497689816#\n").as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},L0#\n")
+        );
+        assert!(
+            timeout(Duration::from_millis(200), read_command(&mut device))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reapplied_settings_wait_for_the_echo() {
+        let mut state = AppState::new();
+        state.settings_policy = low_speed();
+        state.history = Some(History::in_memory().unwrap());
+        let mut device = connect(&state).await;
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},Q0,412,80,28#\n").as_bytes())
+            .await
+            .unwrap();
+        read_until(&mut device, &format!("*SCOS,LZ,{IMEI},S7,0,1,0,0#\n")).await;
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,0,1,0,0#\n").as_bytes())
+            .await
+            .unwrap();
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+        timeout(Duration::from_secs(1), async {
+            while connection.pending_requests() > 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let history = state.history.unwrap();
+        history.flush().await;
+        let commands = history
+            .commands(IMEI, Utc::now() - TimeDelta::minutes(1), Utc::now(), 10)
+            .await
+            .unwrap();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].code, "S7");
+        assert_eq!(commands[0].outcome, CommandOutcome::Replied);
+    }
+
+    #[tokio::test]
+    async fn test_drifted_echo_is_reported() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        state
+            .desired_settings
+            .lock()
+            .unwrap()
+            .insert(IMEI.to_string(), low_speed());
+        let mut events = state.events.subscribe();
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,0,3,0,0#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let event = loop {
+            match timeout(Duration::from_secs(1), events.recv())
+                .await
+                .unwrap()
+                .unwrap()
+            {
+                event @ DeviceEvent::SettingsDrift { .. } => break event,
+                _ => continue,
+            }
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "event": "settings_drift",
+                "imei": IMEI,
+                "desired": {
+                    "headlight_switch": null,
+                    "mode_setting": "low_speed",
+                    "throttle_response": null,
+                    "taillights_flashing": null,
+                },
+                "reported": {
+                    "headlight_switch": null,
+                    "mode_setting": "high_speed",
+                    "throttle_response": null,
+                    "taillights_flashing": null,
+                },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_put_applies_desired_settings() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        state
+            .desired_settings
+            .lock()
+            .unwrap()
+            .insert(IMEI.to_string(), low_speed());
+
+        let request = put_desired(&state, json!({"headlight_switch": "on"})).await;
+        read_until(&mut device, &format!("*SCOS,LZ,{IMEI},S7,2,1,0,0#\n")).await;
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,2,1,0,0#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["desired"]["headlight_switch"], "open");
+        assert_eq!(body["settings"]["mode_setting"], "low_speed");
+    }
+
+    #[tokio::test]
+    async fn test_put_for_offline_scooter_is_kept() {
+        let state = AppState::new();
+        presence::record_seen(&state.presence, IMEI, Utc::now());
+
+        let (status, body) = put_desired(&state, json!({"speed_mode": "low"}))
+            .await
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body["success"], true);
+        assert_eq!(body["desired"]["mode_setting"], "low_speed");
+        assert_eq!(desired_settings::desired(&state, IMEI), low_speed());
+    }
+
+    #[tokio::test]
+    async fn test_put_for_unknown_scooter_is_rejected() {
+        let state = AppState::new();
+
+        let (status, body) = put_desired(&state, json!({"speed_mode": "low"}))
+            .await
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["success"], false);
+        assert!(state.desired_settings.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_clears_desired_settings() {
+        let state = AppState::new();
+        state
+            .desired_settings
+            .lock()
+            .unwrap()
+            .insert(IMEI.to_string(), low_speed());
+
+        let (status, cleared) = body(
+            clear_desired_settings_handler(State(state.clone()), Path(IMEI.to_string())).await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(cleared["desired"]["mode_setting"], Value::Null);
+        assert!(desired_settings::desired(&state, IMEI).is_empty());
+
+        let (status, _) =
+            body(clear_desired_settings_handler(State(state), Path(IMEI.to_string())).await).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod devices_tests {
+    use axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+    };
+    use chrono::Utc;
+
+    use crate::{
+        commands::parser::parse_command,
+        server::{
+            device_handler::device_handler,
+            list_devices_handler::{list_devices_handler, DevicesQuery},
+            presence, registry,
+            tests::support::body,
+            AppState,
+        },
+    };
+
+    const LOCKED: &str = "111111111111111";
+    const CHARGING: &str = "222222222222222";
+
+    /// Records frames as if they had arrived from the scooters.
+    fn state() -> AppState {
+        let state = AppState::new();
+        for frame in [
+            format!("*SCOR,LZ,{LOCKED},H0,1,412,28,15,0#\n"),
+            format!("*SCOR,LZ,{CHARGING},H0,0,398,20,60,1#\n"),
+        ] {
+            let command = parse_command(&frame).unwrap();
+            presence::record_seen(&state.presence, command.imei(), Utc::now());
+            registry::record(&state.registry, command.imei(), &command, Utc::now());
+        }
+        presence::record_disconnected(&state.presence, CHARGING);
+        state
+    }
+
+    async fn list(query: DevicesQuery) -> Vec<String> {
+        let (status, body) = body(list_devices_handler(State(state()), Query(query)).await).await;
+        assert_eq!(status, StatusCode::OK);
+        body["devices"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|device| device["imei"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_list_without_filters_returns_every_device() {
+        assert_eq!(list(DevicesQuery::default()).await, [LOCKED, CHARGING]);
+    }
+
+    #[tokio::test]
+    async fn test_list_filters() {
+        let online = DevicesQuery {
+            online: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(list(online).await, [LOCKED]);
+
+        let unlocked = DevicesQuery {
+            locked: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(list(unlocked).await, [CHARGING]);
+
+        let low_battery = DevicesQuery {
+            battery_below: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(list(low_battery).await, [LOCKED]);
+
+        let charging = DevicesQuery {
+            charging: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(list(charging).await, [CHARGING]);
+    }
+
+    #[tokio::test]
+    async fn test_device_returns_state_and_connection_metadata() {
+        let (status, body) =
+            body(device_handler(State(state()), Path(LOCKED.to_string())).await).await;
+
+        assert_eq!(status, StatusCode::OK);
+        let device = &body["device"];
+        assert_eq!(device["connectivity"], "online");
+        assert_eq!(device["connected"], false);
+        assert!(device["last_frame_at"].is_string());
+        assert_eq!(device["state"]["power"], 15);
+        assert_eq!(device["state"]["status"], "locked");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_device_is_not_found() {
+        let (status, body) =
+            body(device_handler(State(state()), Path("0".to_string())).await).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["success"], false);
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod pending_table_tests {
+    use crate::server::dispatcher::PendingTable;
+
+    const L1_REPLY: &str = "*SCOR,LZ,123456789123456,L1,0,1,1497689816,10#\n";
+    const HEARTBEAT: &str = "*SCOR,LZ,123456789123456,H0,1,412,28,80,0#\n";
+
+    #[test]
+    fn test_resolve_routes_matching_reply_to_waiter() {
+        let mut table = PendingTable::default();
+        let (_, mut reply) = table.register("L1", |_| true);
+
+        assert_eq!(table.resolve(L1_REPLY.to_string()), None);
+        assert_eq!(reply.try_recv().unwrap(), L1_REPLY);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_returns_frames_for_other_commands() {
+        let mut table = PendingTable::default();
+        let (_, mut reply) = table.register("L1", |_| true);
+
+        assert_eq!(
+            table.resolve(HEARTBEAT.to_string()),
+            Some(HEARTBEAT.to_string())
+        );
+        assert!(reply.try_recv().is_err());
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_returns_frames_rejected_by_matcher() {
+        let mut table = PendingTable::default();
+        let (_, _reply) = table.register("L1", |frame| frame.contains(",L1,1,"));
+
+        assert_eq!(
+            table.resolve(L1_REPLY.to_string()),
+            Some(L1_REPLY.to_string())
+        );
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_delivers_to_oldest_waiter_first() {
+        let mut table = PendingTable::default();
+        let (_, mut first) = table.register("L1", |_| true);
+        let (_, mut second) = table.register("L1", |_| true);
+
+        table.resolve(L1_REPLY.to_string());
+
+        assert!(first.try_recv().is_ok());
+        assert!(second.try_recv().is_err());
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_skips_abandoned_waiters() {
+        let mut table = PendingTable::default();
+        let (_, abandoned) = table.register("L1", |_| true);
+        let (_, mut waiting) = table.register("L1", |_| true);
+        drop(abandoned);
+
+        assert_eq!(table.resolve(L1_REPLY.to_string()), None);
+        assert_eq!(waiting.try_recv().unwrap(), L1_REPLY);
+    }
+
+    #[test]
+    fn test_cancel_removes_waiter() {
+        let mut table = PendingTable::default();
+        let (id, _reply) = table.register("L1", |_| true);
+
+        table.cancel("L1", id);
+
+        assert!(table.is_empty());
+        assert_eq!(
+            table.resolve(L1_REPLY.to_string()),
+            Some(L1_REPLY.to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod command_code_tests {
+    use crate::server::dispatcher::command_code;
+
+    #[test]
+    fn test_command_code_with_content() {
+        assert_eq!(
+            command_code("*SCOR,LZ,123456789123456,S7,0,3,0,0#\n"),
+            Some("S7")
+        );
+    }
+
+    #[test]
+    fn test_command_code_without_content() {
+        assert_eq!(command_code("*SCOR,LZ,123456789123456,L0#\n"), Some("L0"));
+    }
+
+    #[test]
+    fn test_command_code_with_truncated_frame() {
+        assert_eq!(command_code("*SCOR,LZ#\n"), None);
+    }
+}
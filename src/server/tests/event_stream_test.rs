@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod event_stream_tests {
+    use std::time::Duration;
+
+    use axum::{routing::get, Router};
+    use futures::StreamExt;
+    use serde_json::Value;
+    use tokio::{net::TcpListener, time::timeout};
+    use tokio_tungstenite::tungstenite::Message;
+
+    use crate::server::{
+        event_stream_handler::{sse_handler, ws_handler, EventFilter},
+        events::{self, DeviceEvent, DisconnectReason},
+        tests::support::IMEI,
+        AppState,
+    };
+
+    const OTHER_IMEI: &str = "999999999999999";
+
+    fn disconnected(imei: &str) -> DeviceEvent {
+        DeviceEvent::Disconnected {
+            imei: imei.to_string(),
+            reason: DisconnectReason::Closed,
+        }
+    }
+
+    fn unlocked(imei: &str) -> DeviceEvent {
+        DeviceEvent::Unlocked {
+            imei: imei.to_string(),
+        }
+    }
+
+    async fn serve(state: &AppState) -> String {
+        let app = Router::new()
+            .route("/events", get(sse_handler))
+            .route("/events/ws", get(ws_handler))
+            .with_state(state.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        address
+    }
+
+    /// Publishes events that the `?imei=IMEI&events=unlocked` filter drops, then one it keeps.
+    async fn publish_once_subscribed(state: &AppState) {
+        while state.events.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        events::publish(&state.events, unlocked(OTHER_IMEI));
+        events::publish(&state.events, disconnected(IMEI));
+        events::publish(&state.events, unlocked(IMEI));
+    }
+
+    #[test]
+    fn test_filter_by_imei_and_event_type() {
+        let filter = EventFilter {
+            imei: Some(format!("{OTHER_IMEI}, {IMEI}")),
+            events: Some("unlocked,alarm".to_string()),
+        };
+
+        assert!(filter.matches(&unlocked(IMEI)));
+        assert!(!filter.matches(&disconnected(IMEI)));
+        assert!(!filter.matches(&unlocked("111111111111111")));
+        assert!(EventFilter::default().matches(&disconnected(IMEI)));
+    }
+
+    #[tokio::test]
+    async fn test_sse_streams_filtered_events() {
+        let state = AppState::new();
+        let address = serve(&state).await;
+
+        let mut response = reqwest::get(format!(
+            "http://{address}/events?imei={IMEI}&events=unlocked"
+        ))
+        .await
+        .unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        publish_once_subscribed(&state).await;
+
+        let chunk = timeout(Duration::from_secs(1), response.chunk())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let chunk = String::from_utf8_lossy(&chunk).to_string();
+        assert!(chunk.starts_with("event: unlocked\n"), "{chunk}");
+        assert!(chunk.contains(&format!(r#"data: {{"event":"unlocked","imei":"{IMEI}"}}"#)));
+    }
+
+    #[tokio::test]
+    async fn test_websocket_streams_filtered_events() {
+        let state = AppState::new();
+        let address = serve(&state).await;
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!(
+            "ws://{address}/events/ws?imei={IMEI}&events=unlocked"
+        ))
+        .await
+        .unwrap();
+        publish_once_subscribed(&state).await;
+
+        let message = timeout(Duration::from_secs(1), socket.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let Message::Text(text) = message else {
+            panic!("Expected a text message, got {message:?}");
+        };
+        let event: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(event["event"], "unlocked");
+        assert_eq!(event["imei"], IMEI);
+    }
+}
@@ -0,0 +1,519 @@
+#[cfg(test)]
+mod handle_connection_tests {
+    use std::{sync::Arc, time::Duration};
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        time::timeout,
+    };
+
+    use axum::http::StatusCode;
+
+    use crate::{
+        commands::scooter_command::ScooterCommand,
+        errors::AppError,
+        server::{
+            events::{DeviceEvent, DisconnectReason},
+            handler::{get_client, send_command, send_request},
+            presence::Connectivity,
+            registry,
+            tests::support::{connect, read_command, read_until, sign_in, IMEI},
+            AppState,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_frames_after_sign_in_are_published() {
+        let state = AppState::new();
+        let mut events = state.events.subscribe();
+        let mut device = connect(&state).await;
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},Q0,412,80,28#\n").as_bytes())
+            .await
+            .unwrap();
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            event,
+            Ok(DeviceEvent::Frame {
+                command: ScooterCommand::SigningIn { .. },
+                ..
+            })
+        ));
+        assert!(state.clients.lock().await.contains_key(IMEI));
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},H0,1,412,28,80,0#\n").as_bytes())
+            .await
+            .unwrap();
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap();
+        match event {
+            Ok(DeviceEvent::Frame {
+                imei,
+                command: ScooterCommand::HeartBeat { power, .. },
+            }) => {
+                assert_eq!(imei, IMEI);
+                assert_eq!(power, 80);
+            }
+            other => panic!("Unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_records_peer_metadata() {
+        let state = AppState::new();
+        let device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+
+        assert_eq!(connection.peer_address, Some(device.local_addr().unwrap()));
+        assert!(connection.connected_since <= chrono::Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_sign_in_is_not_registered() {
+        let state = AppState::new();
+        let mut device = connect(&state).await;
+
+        device.write_all(b"*SCOR,LZ,123,H0#\n").await.unwrap();
+        // The server hangs up on it
+        let mut buffer = [0; 16];
+        let n = timeout(Duration::from_secs(1), device.read(&mut buffer))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(n, 0);
+
+        assert!(state.clients.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_request_receives_reply_while_frames_are_published() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        let mut events = state.events.subscribe();
+
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+        let request = tokio::spawn(async move {
+            let command = format!("*SCOS,LZ,{IMEI},S7,0,3,0,0#\n");
+            send_request(&connection, &command, "S7", Duration::from_secs(1), |_| {
+                true
+            })
+            .await
+        });
+
+        // Wait for the command before answering with a heartbeat and then the reply
+        assert!(read_command(&mut device).await.contains(",S7,"));
+        let reply = format!("*SCOR,LZ,{IMEI},S7,0,3,0,0#\n");
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},H0,1,412,28,80,0#\n{reply}").as_bytes())
+            .await
+            .unwrap();
+
+        let response = timeout(Duration::from_secs(1), request).await.unwrap();
+        assert_eq!(response.unwrap().unwrap(), reply);
+
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            event,
+            Ok(DeviceEvent::Frame {
+                command: ScooterCommand::HeartBeat { .. },
+                ..
+            })
+        ));
+        // The claimed reply is published as well
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            event,
+            Ok(DeviceEvent::Frame {
+                command: ScooterCommand::ScooterSetting { .. },
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_request_times_out_when_device_does_not_answer() {
+        let state = AppState::new();
+        let _device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+
+        let command = format!("*SCOS,LZ,{IMEI},L1,55#\n");
+        let result = send_request(
+            &connection,
+            &command,
+            "L1",
+            Duration::from_millis(50),
+            |_| true,
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(err, AppError::DeviceTimeout(_)));
+        assert_eq!(err.status_code(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(connection.pending_requests(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_releases_pending_slot() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+
+        let request_connection = connection.clone();
+        let request = tokio::spawn(async move {
+            let command = format!("*SCOS,LZ,{IMEI},L1,55#\n");
+            send_request(
+                &request_connection,
+                &command,
+                "L1",
+                Duration::from_secs(60),
+                |_| true,
+            )
+            .await
+        });
+        // The request is registered before its command is written
+        read_until(&mut device, ",L1,55#\n").await;
+        assert_eq!(connection.pending_requests(), 1);
+
+        request.abort();
+        let _ = request.await;
+
+        assert_eq!(connection.pending_requests(), 0);
+        assert!(send_command(&connection, "*SCOS,LZ,1,L1#\n").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_closed_connection_is_evicted() {
+        let state = AppState::new();
+        let device = sign_in(&state).await;
+        let mut events = state.events.subscribe();
+
+        drop(device);
+
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            event,
+            Ok(DeviceEvent::Disconnected {
+                reason: DisconnectReason::Closed,
+                ..
+            })
+        ));
+        assert!(state.clients.lock().await.is_empty());
+
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            event,
+            Ok(DeviceEvent::ConnectivityChanged {
+                from: Connectivity::Online,
+                to: Connectivity::Offline,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_silent_connection_is_evicted() {
+        let mut state = AppState::new();
+        state.idle_timeout = Duration::from_millis(50);
+        let _device = sign_in(&state).await;
+        let mut events = state.events.subscribe();
+
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            event,
+            Ok(DeviceEvent::Disconnected {
+                reason: DisconnectReason::IdleTimeout,
+                ..
+            })
+        ));
+        assert!(state.clients.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stalled_write_times_out_and_drops_connection() {
+        let mut state = AppState::new();
+        state.write_timeout = Duration::from_millis(100);
+        // The scooter stops reading, so a large enough command cannot be written
+        let _device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+        let mut events = state.events.subscribe();
+
+        let command = "x".repeat(64 * 1024 * 1024);
+        let result = timeout(Duration::from_secs(5), send_command(&connection, &command))
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(AppError::DeviceTimeout(_))));
+
+        loop {
+            let event = timeout(Duration::from_secs(1), events.recv())
+                .await
+                .unwrap();
+            if let Ok(DeviceEvent::Disconnected { reason, .. }) = event {
+                assert_eq!(reason, DisconnectReason::WriteTimeout);
+                break;
+            }
+        }
+        assert!(state.clients.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pending_request_fails_when_connection_drops() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+
+        let request = tokio::spawn(async move {
+            let command = format!("*SCOS,LZ,{IMEI},L1,55#\n");
+            send_request(&connection, &command, "L1", Duration::from_secs(60), |_| {
+                true
+            })
+            .await
+        });
+        read_until(&mut device, ",L1,55#\n").await;
+        drop(device);
+
+        let result = timeout(Duration::from_secs(1), request).await.unwrap();
+        assert!(matches!(result.unwrap(), Err(AppError::SocketError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_from_new_socket_replaces_stale_connection() {
+        let state = AppState::new();
+        let mut events = state.events.subscribe();
+        let mut stale_device = sign_in(&state).await;
+        let stale = get_client(&state.clients, IMEI).await.unwrap();
+
+        let _device = sign_in(&state).await;
+        let current = get_client(&state.clients, IMEI).await.unwrap();
+        assert!(!Arc::ptr_eq(&stale, &current));
+
+        // The old socket is closed by the server
+        let mut buffer = [0; 16];
+        let n = timeout(Duration::from_secs(1), stale_device.read(&mut buffer))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(n, 0);
+
+        // The scooter never went offline, so subscribers are not told it disconnected
+        while let Ok(event) = timeout(Duration::from_millis(200), events.recv()).await {
+            assert!(!matches!(event, Ok(DeviceEvent::Disconnected { .. })));
+        }
+        let clients = state.clients.lock().await;
+        assert!(Arc::ptr_eq(clients.get(IMEI).unwrap(), &current));
+        assert_eq!(
+            state.presence.lock().unwrap()[IMEI].connectivity,
+            Connectivity::Online
+        );
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_and_alarm_are_acknowledged() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},H0,1,412,28,80,0#\n").as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},H0#\n")
+        );
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},W0,1#\n").as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},W0#\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unsolicited_unlock_and_lock_are_acknowledged() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        // e.g. an RFID card unlock, with no request waiting for it
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},L0,0,1234,1497689816#\n").as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},L0#\n")
+        );
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},L1,0,1234,1497689816,3#\n").as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},L1#\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_frames_for_another_imei_are_dropped() {
+        const OTHER: &str = "999999999999999";
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        let mut events = state.events.subscribe();
+
+        device
+            .write_all(format!("*SCOR,LZ,{OTHER},H0,0,398,20,15,1#\n").as_bytes())
+            .await
+            .unwrap();
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},H0,1,412,28,80,0#\n").as_bytes())
+            .await
+            .unwrap();
+
+        // Only the frame for the signed-in scooter gets through
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(event, DeviceEvent::Frame { ref imei, .. } if imei == IMEI),
+            "{:?}",
+            event
+        );
+        assert!(registry::get(&state.registry, OTHER).is_none());
+        assert_eq!(
+            registry::get(&state.registry, IMEI).unwrap().power,
+            Some(80)
+        );
+        // The foreign heartbeat was not acknowledged
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},H0#\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_claimed_reply_updates_registry() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+
+        let request = tokio::spawn(async move {
+            let command = format!("*SCOS,LZ,{IMEI},S7,0,3,0,0#\n");
+            send_request(&connection, &command, "S7", Duration::from_secs(1), |_| {
+                true
+            })
+            .await
+        });
+        assert!(read_command(&mut device).await.contains(",S7,"));
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,0,3,0,0#\n").as_bytes())
+            .await
+            .unwrap();
+        timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        let device_state = registry::get(&state.registry, IMEI).unwrap();
+        assert_eq!(device_state.power, Some(80));
+        assert!(device_state.settings.mode_setting.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_claimed_reply_is_published() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+        let mut events = state.events.subscribe();
+
+        let request = tokio::spawn(async move {
+            let command = format!("*SCOS,LZ,{IMEI},D0#\n");
+            send_request(&connection, &command, "D0", Duration::from_secs(1), |_| {
+                true
+            })
+            .await
+        });
+        assert!(read_command(&mut device).await.contains(",D0#"));
+        device
+            .write_all(
+                format!("*SCOR,LZ,{IMEI},D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n")
+                    .as_bytes(),
+            )
+            .await
+            .unwrap();
+        timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(
+                event,
+                DeviceEvent::Frame {
+                    command: ScooterCommand::PositioningResponse(_),
+                    ..
+                }
+            ),
+            "{:?}",
+            event
+        );
+    }
+
+    #[tokio::test]
+    async fn test_claimed_lock_reply_publishes_completion() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+        let mut events = state.events.subscribe();
+
+        let request = tokio::spawn(async move {
+            let command = format!("*SCOS,LZ,{IMEI},L1,55#\n");
+            send_request(&connection, &command, "L1", Duration::from_secs(1), |_| {
+                true
+            })
+            .await
+        });
+        assert!(read_command(&mut device).await.contains(",L1,"));
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},L1,0,1,1497689816,12#\n").as_bytes())
+            .await
+            .unwrap();
+        timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        let event = timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            event,
+            DeviceEvent::Locked { ref imei, cycling_time: 12 } if imei == IMEI
+        ));
+    }
+}
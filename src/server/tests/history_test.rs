@@ -0,0 +1,278 @@
+#[cfg(test)]
+mod history_tests {
+    use std::time::Duration;
+
+    use axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+    };
+    use chrono::{DateTime, TimeDelta, Utc};
+    use tokio::io::AsyncWriteExt;
+
+    use crate::{
+        commands::{
+            alarm_command::AlarmType, hearbeat_command::ScooterStatus, parser::parse_command,
+        },
+        server::{
+            alarms::Severity,
+            events::DeviceEvent,
+            handler::{get_client, send_request},
+            history::{CommandOutcome, HeartbeatRecord, History},
+            history_handler::{heartbeat_history_handler, HistoryQuery},
+            tests::support::{body, read_command, read_until, sign_in, IMEI},
+            AppState,
+        },
+    };
+
+    const HEARTBEAT: &str = "*SCOR,LZ,123456789123456,H0,1,412,28,80,0#\n";
+    const FIX: &str = "*SCOR,LZ,123456789123456,D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n";
+    const NO_FIX: &str =
+        "*SCOR,LZ,123456789123456,D0,0,130000.00,V,0000.0000,N,00000.0000,E,0,0,151216,0,M,N#\n";
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap() + TimeDelta::minutes(minutes)
+    }
+
+    async fn record(history: &History, frame: &str, now: DateTime<Utc>) {
+        let command = parse_command(frame).unwrap();
+        history.record_frame(command.imei(), &command, now);
+        history.flush().await;
+    }
+
+    fn history_state() -> AppState {
+        let mut state = AppState::new();
+        state.history = Some(History::in_memory().unwrap());
+        state
+    }
+
+    #[tokio::test]
+    async fn test_frames_from_scooter_are_recorded() {
+        let state = history_state();
+        let mut device = sign_in(&state).await;
+        let mut events = state.events.subscribe();
+
+        for frame in [HEARTBEAT, FIX, "*SCOR,LZ,123456789123456,W0,1#\n"] {
+            device.write_all(frame.as_bytes()).await.unwrap();
+        }
+        read_until(&mut device, "W0#\n").await;
+        // Frame events are published once the frame was queued for storage
+        loop {
+            if let DeviceEvent::Frame { command, .. } = events.recv().await.unwrap() {
+                if command.code() == "W0" {
+                    break;
+                }
+            }
+        }
+
+        let history = state.history.unwrap();
+        history.flush().await;
+        let (from, to) = (Utc::now() - TimeDelta::minutes(1), Utc::now());
+        let heartbeats = history.heartbeats(IMEI, from, to, 10).await.unwrap();
+        assert_eq!(heartbeats.len(), 1);
+        assert_eq!(heartbeats[0].status, ScooterStatus::Locked);
+        assert_eq!(heartbeats[0].voltage, 4.12);
+        assert_eq!(heartbeats[0].power, 80);
+
+        let positions = history.positions(IMEI, from, to, 10).await.unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].satellites, 6);
+        assert!((positions[0].latitude - 22.62919).abs() < 1e-5);
+
+        let alarms = history.alarms(IMEI, from, to, 10).await.unwrap();
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].alarm_type, AlarmType::IllegalMovement);
+        assert_eq!(alarms[0].severity, Severity::Warning);
+    }
+
+    #[tokio::test]
+    async fn test_queries_filter_by_device_and_range() {
+        let history = History::in_memory().unwrap();
+        record(&history, HEARTBEAT, at(0)).await;
+        record(&history, HEARTBEAT, at(10)).await;
+        record(&history, HEARTBEAT, at(20)).await;
+        record(
+            &history,
+            "*SCOR,LZ,999999999999999,H0,0,398,20,60,1#\n",
+            at(10),
+        )
+        .await;
+        // Fixes without a position are not history
+        record(&history, NO_FIX, at(10)).await;
+
+        let recorded_at = |records: Vec<HeartbeatRecord>| {
+            records
+                .into_iter()
+                .map(|record| record.recorded_at)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            recorded_at(history.heartbeats(IMEI, at(0), at(20), 10).await.unwrap()),
+            [at(0), at(10), at(20)]
+        );
+        assert_eq!(
+            recorded_at(history.heartbeats(IMEI, at(5), at(15), 10).await.unwrap()),
+            [at(10)]
+        );
+        assert_eq!(
+            recorded_at(history.heartbeats(IMEI, at(0), at(20), 2).await.unwrap()),
+            [at(0), at(10)]
+        );
+        assert!(history
+            .positions(IMEI, at(0), at(20), 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prune_removes_records_before_cutoff() {
+        let history = History::in_memory().unwrap();
+        record(&history, HEARTBEAT, at(0)).await;
+        record(&history, FIX, at(0)).await;
+        record(&history, HEARTBEAT, at(30)).await;
+
+        assert_eq!(history.prune(at(10)).await.unwrap(), 2);
+        let heartbeats = history.heartbeats(IMEI, at(0), at(30), 10).await.unwrap();
+        assert_eq!(heartbeats.len(), 1);
+        assert_eq!(heartbeats[0].recorded_at, at(30));
+    }
+
+    #[tokio::test]
+    async fn test_queries_do_not_wait_for_the_writer() {
+        let path = std::env::temp_dir().join(format!("history-test-{}.db", std::process::id()));
+        let history = History::open(path.to_str().unwrap()).unwrap();
+        record(&history, HEARTBEAT, at(0)).await;
+
+        // Hold the writer up in the middle of a transaction
+        let (started, writing) = std::sync::mpsc::channel();
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        history.write("a slow write".to_string(), move |db| {
+            db.execute_batch("BEGIN; DELETE FROM heartbeats;")?;
+            started.send(()).unwrap();
+            let _ = released.recv();
+            db.execute_batch("ROLLBACK;")?;
+            Ok(0)
+        });
+        writing.recv().unwrap();
+
+        let heartbeats = tokio::time::timeout(
+            Duration::from_secs(1),
+            history.heartbeats(IMEI, at(0), at(10), 10),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(heartbeats.len(), 1);
+
+        release.send(()).unwrap();
+        history.flush().await;
+        drop(history);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commands_are_audited() {
+        let state = history_state();
+        let mut device = sign_in(&state).await;
+        let connection = get_client(&state.clients, IMEI).await.unwrap();
+
+        let command = format!("0xFFFF*SCOS,LZ,{IMEI},S6#\n");
+        let request = {
+            let (connection, command) = (connection.clone(), command.clone());
+            tokio::spawn(async move {
+                send_request(&connection, &command, "S6", Duration::from_secs(1), |_| {
+                    true
+                })
+                .await
+            })
+        };
+        assert_eq!(read_command(&mut device).await, command);
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S6,80,3,22,0,372,0,0,28,42#\n").as_bytes())
+            .await
+            .unwrap();
+        request.await.unwrap().unwrap();
+
+        let silent = send_request(
+            &connection,
+            &format!("0xFFFF*SCOS,LZ,{IMEI},D0#\n"),
+            "D0",
+            Duration::from_millis(50),
+            |_| true,
+        )
+        .await;
+        assert!(silent.is_err());
+
+        let history = state.history.unwrap();
+        history.flush().await;
+        let commands = history
+            .commands(IMEI, Utc::now() - TimeDelta::minutes(1), Utc::now(), 10)
+            .await
+            .unwrap();
+        // The Q0 acknowledgement sent on sign-in is not audited
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].code, "S6");
+        assert_eq!(commands[0].command, format!("0xFFFF*SCOS,LZ,{IMEI},S6#"));
+        assert_eq!(commands[0].outcome, CommandOutcome::Replied);
+        assert_eq!(
+            commands[0].reply.as_deref(),
+            Some(format!("*SCOR,LZ,{IMEI},S6,80,3,22,0,372,0,0,28,42#").as_str())
+        );
+        assert_eq!(commands[1].code, "D0");
+        assert_eq!(commands[1].outcome, CommandOutcome::TimedOut);
+        assert!(commands[1].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_history_endpoint_returns_records_in_range() {
+        let state = history_state();
+        let history = state.history.clone().unwrap();
+        record(&history, HEARTBEAT, at(0)).await;
+        record(&history, HEARTBEAT, at(10)).await;
+
+        let query = HistoryQuery {
+            from: Some(at(5)),
+            to: Some(at(15)),
+            limit: None,
+        };
+        let (status, body) = body(
+            heartbeat_history_handler(State(state), Path(IMEI.to_string()), Query(query)).await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+        assert_eq!(body["records"].as_array().unwrap().len(), 1);
+        assert_eq!(body["records"][0]["recorded_at"], "2023-11-14T22:23:20Z");
+        assert_eq!(body["records"][0]["charging"], "uncharged");
+    }
+
+    #[tokio::test]
+    async fn test_history_endpoint_rejects_bad_requests() {
+        let query = HistoryQuery {
+            from: Some(at(10)),
+            to: Some(at(0)),
+            limit: None,
+        };
+        let (status, _) = body(
+            heartbeat_history_handler(State(history_state()), Path(IMEI.to_string()), Query(query))
+                .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // History is off unless HISTORY_DB_PATH is set
+        let (status, body) = body(
+            heartbeat_history_handler(
+                State(AppState::new()),
+                Path(IMEI.to_string()),
+                Query(HistoryQuery::default()),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["success"], false);
+    }
+}
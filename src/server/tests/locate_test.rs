@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod locate_tests {
+    use std::time::Duration;
+
+    use axum::{extract::State, http::StatusCode, Json};
+    use serde_json::Value;
+    use tokio::{io::AsyncWriteExt, net::TcpStream, time::timeout};
+
+    use crate::server::{
+        locate_handler::{locate_handler, LocateRequest},
+        tests::support::{body, read_command, sign_in, IMEI},
+        AppState,
+    };
+
+    const FIX: &str = "124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A";
+    const NO_FIX: &str = "130000.00,V,0000.0000,N,00000.0000,E,0,0,151216,0,M,N";
+
+    async fn locate(state: &AppState) -> tokio::task::JoinHandle<(StatusCode, Value)> {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let request = Json(LocateRequest {
+                imei: IMEI.to_string(),
+            });
+            body(locate_handler(State(state), request).await).await
+        })
+    }
+
+    async fn answer(device: &mut TcpStream, identifier: u8, fix: &str) {
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},D0,{identifier},{fix}#\n").as_bytes())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_locate_returns_fresh_fix() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        let request = locate(&state).await;
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},D0#\n")
+        );
+        // A tracking report is not the answer to the request
+        answer(&mut device, 1, NO_FIX).await;
+        answer(&mut device, 0, FIX).await;
+
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["stale"], false);
+        assert!((body["position"]["latitude"].as_f64().unwrap() - 22.62919).abs() < 1e-5);
+        assert_eq!(body["position"]["satellites_number"], 6);
+        assert_eq!(body["position"]["mode"], "autonomous");
+    }
+
+    #[tokio::test]
+    async fn test_locate_without_fix_falls_back_to_last_known_position() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+        answer(&mut device, 1, FIX).await;
+
+        let request = locate(&state).await;
+        read_command(&mut device).await;
+        answer(&mut device, 0, NO_FIX).await;
+
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["stale"], true);
+        assert_eq!(body["position"]["fixed_at"], "2016-12-15T12:44:58Z");
+    }
+
+    #[tokio::test]
+    async fn test_locate_without_any_fix_is_unavailable() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        let request = locate(&state).await;
+        read_command(&mut device).await;
+        answer(&mut device, 0, NO_FIX).await;
+
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body["position"].is_null());
+    }
+}
@@ -1,2 +1,23 @@
+pub mod alarms_test;
+pub mod beep_test;
+pub mod codec_test;
 pub mod commands_test;
+pub mod desired_settings_test;
+pub mod devices_test;
+pub mod dispatcher_test;
+pub mod event_stream_test;
+pub mod handler_test;
+pub mod history_test;
+pub mod locate_test;
+#[cfg(feature = "mqtt")]
+pub mod mqtt_test;
+pub mod parser_service_test;
+pub mod presence_test;
 pub mod protocol_test;
+pub mod registry_test;
+pub mod settings_test;
+pub mod status_test;
+#[cfg(test)]
+pub mod support;
+pub mod tracking_test;
+pub mod webhooks_test;
@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod mqtt_tests {
+    use std::time::Duration;
+
+    use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+    use serde_json::{json, Value};
+    use tokio::{
+        io::AsyncWriteExt,
+        sync::{
+            mpsc::{self, UnboundedReceiver},
+            oneshot,
+        },
+        time::timeout,
+    };
+
+    use crate::server::{
+        events::{DeviceEvent, DisconnectReason},
+        mqtt::{self, event_topic, parse_command_topic, reply_topic},
+        tests::support::{read_command, sign_in, IMEI},
+        AppState,
+    };
+
+    #[test]
+    fn test_event_topics() {
+        let disconnected = DeviceEvent::Disconnected {
+            imei: IMEI.to_string(),
+            reason: DisconnectReason::Closed,
+        };
+        assert_eq!(
+            event_topic(&disconnected),
+            Some(format!("scooters/{IMEI}/disconnected"))
+        );
+        assert_eq!(
+            reply_topic(IMEI, "unlock"),
+            format!("scooters/{IMEI}/cmd/unlock/reply")
+        );
+    }
+
+    #[test]
+    fn test_command_topics_are_parsed() {
+        assert_eq!(
+            parse_command_topic(&format!("scooters/{IMEI}/cmd/gear")),
+            Some((IMEI, "gear"))
+        );
+        assert_eq!(
+            parse_command_topic(&format!("scooters/{IMEI}/cmd/gear/reply")),
+            None
+        );
+        assert_eq!(parse_command_topic("scooters//cmd/gear"), None);
+    }
+
+    #[tokio::test]
+    async fn test_command_runs_through_handler() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        let command = {
+            let state = state.clone();
+            tokio::spawn(async move {
+                mqtt::run_command(&state, IMEI, "light", br#"{"state":true,"request_id":7}"#).await
+            })
+        };
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},S7,2,0,0,0#\n")
+        );
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,2,0,0,0#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let reply = timeout(Duration::from_secs(1), command)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reply["status"], 200);
+        assert_eq!(reply["success"], true);
+        assert_eq!(reply["request_id"], 7);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_commands_are_rejected() {
+        let state = AppState::new();
+
+        let reply = mqtt::run_command(&state, IMEI, "gear", br#"{"gear":"fast"}"#).await;
+        assert_eq!(reply["status"], 400);
+        let reply = mqtt::run_command(&state, IMEI, "honk", b"").await;
+        assert_eq!(reply["status"], 404);
+        let reply = mqtt::run_command(&state, IMEI, "unlock", b"").await;
+        assert_eq!(reply["status"], 404);
+        assert_eq!(reply["success"], false);
+    }
+
+    /// Drives the test client's event loop, forwarding every message it receives.
+    async fn subscribe(host: &str, port: u16) -> (AsyncClient, UnboundedReceiver<(String, Value)>) {
+        let (client, mut eventloop) =
+            AsyncClient::new(MqttOptions::new("bridge-test", host, port), 16);
+        client
+            .subscribe(format!("scooters/{IMEI}/#"), QoS::AtLeastOnce)
+            .await
+            .unwrap();
+
+        let (sender, messages) = mpsc::unbounded_channel();
+        let (subscribed, ready) = oneshot::channel();
+        tokio::spawn(async move {
+            let mut subscribed = Some(subscribed);
+            while let Ok(event) = eventloop.poll().await {
+                match event {
+                    Event::Incoming(Packet::SubAck(_)) => {
+                        subscribed.take().map(|subscribed| subscribed.send(()));
+                    }
+                    Event::Incoming(Packet::Publish(publish)) => {
+                        let payload = serde_json::from_slice(&publish.payload).unwrap();
+                        let _ = sender.send((publish.topic.clone(), payload));
+                    }
+                    _ => {}
+                }
+            }
+        });
+        timeout(Duration::from_secs(5), ready)
+            .await
+            .unwrap()
+            .unwrap();
+        (client, messages)
+    }
+
+    /// Waits for the next message published on `topic`.
+    async fn next_publish(messages: &mut UnboundedReceiver<(String, Value)>, topic: &str) -> Value {
+        loop {
+            let (received, payload) = timeout(Duration::from_secs(5), messages.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if received == topic {
+                return payload;
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an MQTT broker at MQTT_TEST_BROKER, e.g. `docker compose --profile mqtt up mosquitto`"]
+    async fn test_bridge_against_local_broker() {
+        let broker = std::env::var("MQTT_TEST_BROKER").unwrap_or("localhost:1883".to_string());
+        let (host, port) = broker.split_once(':').unwrap();
+        let port: u16 = port.parse().unwrap();
+
+        let state = AppState::new();
+        tokio::spawn(mqtt::run(state.clone(), host.to_string(), port));
+        let (client, mut messages) = subscribe(host, port).await;
+        // Let the bridge connect and subscribe before anything is published
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let mut device = sign_in(&state).await;
+        let event = next_publish(&mut messages, &format!("scooters/{IMEI}/sign_in")).await;
+        assert_eq!(event["command"]["power"], 80);
+
+        client
+            .publish(
+                format!("scooters/{IMEI}/cmd/gear"),
+                QoS::AtLeastOnce,
+                false,
+                json!({"gear": 1}).to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},S7,0,1,0,0#\n")
+        );
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,0,1,0,0#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let reply = next_publish(&mut messages, &format!("scooters/{IMEI}/cmd/gear/reply")).await;
+        assert_eq!(reply["success"], true);
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod parser_service_tests {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    use crate::server::parser_service::handle_parser_connection;
+
+    async fn decode_lines(input: &[u8]) -> Vec<serde_json::Value> {
+        let (mut client, server) = tokio::io::duplex(4096);
+        let service = tokio::spawn(handle_parser_connection(server));
+
+        client.write_all(input).await.unwrap();
+        client.shutdown().await.unwrap();
+
+        let mut lines = BufReader::new(client).lines();
+        let mut decoded = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            decoded.push(serde_json::from_str(&line).unwrap());
+        }
+        service.await.unwrap().unwrap();
+        decoded
+    }
+
+    #[tokio::test]
+    async fn test_valid_frames_are_decoded_one_per_line() {
+        let decoded = decode_lines(
+            b"*SCOR,LZ,123456789123456,Q0,412,80,28#\n*SCOR,LZ,123456789123456,W0,1#\n",
+        )
+        .await;
+
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0]["result"], "ok");
+        assert_eq!(decoded[0]["command"]["type"], "signing_in");
+        assert_eq!(decoded[0]["command"]["power"], 80);
+        assert_eq!(decoded[1]["result"], "ok");
+        assert_eq!(decoded[1]["frame"], "*SCOR,LZ,123456789123456,W0,1#\n");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_frame_returns_structured_error() {
+        let decoded = decode_lines(b"*SCOR,LZ,123456789123456,ZZ,1#\n").await;
+
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0]["result"], "error");
+        assert_eq!(decoded[0]["frame"], "*SCOR,LZ,123456789123456,ZZ,1#\n");
+        assert_eq!(decoded[0]["kind"], "unknown_command");
+        assert_eq!(decoded[0]["error"], "Unknown command: ZZ");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_is_reported_and_decoding_continues() {
+        let mut input = vec![b'A'; 2048];
+        input.extend_from_slice(b"#\n*SCOR,LZ,123456789123456,W0,1#\n");
+
+        let decoded = decode_lines(&input).await;
+
+        assert_eq!(decoded[0]["result"], "error");
+        assert!(decoded[0]["frame"].is_null());
+        assert_eq!(decoded.last().unwrap()["result"], "ok");
+    }
+}
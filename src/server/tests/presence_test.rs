@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod heartbeat_policy_tests {
+    use std::time::Duration;
+
+    use crate::server::presence::{Connectivity, HeartbeatPolicy};
+
+    fn policy() -> HeartbeatPolicy {
+        HeartbeatPolicy {
+            interval: Duration::from_secs(60),
+            grace: Duration::from_secs(10),
+            missed_before_offline: 3,
+        }
+    }
+
+    #[test]
+    fn test_connectivity_within_interval_and_grace() {
+        assert_eq!(
+            policy().connectivity(Duration::from_secs(70)),
+            Connectivity::Online
+        );
+    }
+
+    #[test]
+    fn test_connectivity_after_missed_heartbeat() {
+        assert_eq!(
+            policy().connectivity(Duration::from_secs(71)),
+            Connectivity::Degraded
+        );
+        assert_eq!(
+            policy().connectivity(Duration::from_secs(190)),
+            Connectivity::Degraded
+        );
+    }
+
+    #[test]
+    fn test_connectivity_after_too_many_missed_heartbeats() {
+        assert_eq!(
+            policy().connectivity(Duration::from_secs(191)),
+            Connectivity::Offline
+        );
+    }
+}
+
+#[cfg(test)]
+mod presence_map_tests {
+    use std::time::Duration;
+
+    use chrono::{TimeDelta, Utc};
+
+    use crate::server::presence::{
+        record_disconnected, record_seen, refresh, Connectivity, HeartbeatPolicy, PresenceMap,
+    };
+
+    const IMEI: &str = "123456789123456";
+
+    fn policy() -> HeartbeatPolicy {
+        HeartbeatPolicy {
+            interval: Duration::from_secs(60),
+            grace: Duration::from_secs(10),
+            missed_before_offline: 3,
+        }
+    }
+
+    #[test]
+    fn test_first_frame_is_not_a_transition() {
+        let presence = PresenceMap::default();
+
+        assert_eq!(record_seen(&presence, IMEI, Utc::now()), None);
+        assert_eq!(
+            presence.lock().unwrap()[IMEI].connectivity,
+            Connectivity::Online
+        );
+    }
+
+    #[test]
+    fn test_refresh_reports_scooter_going_dark() {
+        let presence = PresenceMap::default();
+        let seen = Utc::now();
+        record_seen(&presence, IMEI, seen);
+
+        assert!(refresh(&presence, &policy(), seen + TimeDelta::seconds(30)).is_empty());
+
+        let transitions = refresh(&presence, &policy(), seen + TimeDelta::seconds(100));
+        assert_eq!(
+            transitions,
+            vec![(
+                IMEI.to_string(),
+                (Connectivity::Online, Connectivity::Degraded)
+            )]
+        );
+
+        let transitions = refresh(&presence, &policy(), seen + TimeDelta::seconds(300));
+        assert_eq!(
+            transitions,
+            vec![(
+                IMEI.to_string(),
+                (Connectivity::Degraded, Connectivity::Offline)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_frame_after_silence_brings_scooter_back_online() {
+        let presence = PresenceMap::default();
+        let seen = Utc::now();
+        record_seen(&presence, IMEI, seen);
+        refresh(&presence, &policy(), seen + TimeDelta::seconds(100));
+
+        assert_eq!(
+            record_seen(&presence, IMEI, seen + TimeDelta::seconds(101)),
+            Some((Connectivity::Degraded, Connectivity::Online))
+        );
+    }
+
+    #[test]
+    fn test_disconnect_marks_scooter_offline() {
+        let presence = PresenceMap::default();
+        let seen = Utc::now();
+        record_seen(&presence, IMEI, seen);
+
+        assert_eq!(
+            record_disconnected(&presence, IMEI),
+            Some((Connectivity::Online, Connectivity::Offline))
+        );
+        assert_eq!(record_disconnected(&presence, IMEI), None);
+
+        // A disconnected scooter stays offline even if its last frame was recent
+        assert!(refresh(&presence, &policy(), seen).is_empty());
+        assert!(!presence.lock().unwrap()[IMEI].connected);
+    }
+
+    #[test]
+    fn test_disconnect_of_unknown_scooter() {
+        let presence = PresenceMap::default();
+
+        assert_eq!(record_disconnected(&presence, IMEI), None);
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod validate_l0_response_tests {
-    use crate::server::{commands::R0Operation, protocol::validate_l0_response};
+    use crate::{commands::unlock_command::R0Operation, server::protocol::validate_l0_response};
 
     #[test]
     fn test_validate_l0_response_valid() {
@@ -23,7 +23,7 @@ mod validate_l0_response_tests {
     #[test]
     fn test_validate_l0_response_invalid_content() {
         let imei = "123456789123456";
-        let invalid_operation: &str = (&R0Operation::Lock).try_into().unwrap();
+        let invalid_operation: &str = (&R0Operation::Lock).into();
         let user_id = 1234u32;
         let timestamp = 1497689816;
         let response = format!(
@@ -43,13 +43,13 @@ mod validate_l0_response_tests {
 
 #[cfg(test)]
 mod validate_r0_response_tests {
-    use crate::server::{commands::R0Operation, protocol::validate_r0_response};
+    use crate::{commands::unlock_command::R0Operation, server::protocol::validate_r0_response};
 
     #[test]
     fn test_validate_r0_response_unlock_valid() {
         let imei = "123456789123456";
         let r0_operation = R0Operation::Unlock;
-        let operation: &str = (&r0_operation).try_into().unwrap();
+        let operation: &str = (&r0_operation).into();
         let user_id = 1234u32;
         let timestamp = 1497689816;
         let response = format!(
@@ -70,7 +70,7 @@ mod validate_r0_response_tests {
     fn test_validate_r0_response_lock_valid() {
         let imei = "123456789123456";
         let r0_operation = R0Operation::Lock;
-        let operation: &str = (&r0_operation).try_into().unwrap();
+        let operation: &str = (&r0_operation).into();
         let user_id = 1234u32;
         let timestamp = 1497689816;
         let response = format!(
@@ -96,7 +96,7 @@ mod validate_r0_response_tests {
         let timestamp = 1497689816;
         let response = "*SCOR,INVALID,123456789123456,R0,0,55,1234,1497689816#\n";
 
-        let result = validate_r0_response(&response, imei, &r0_operation, user_id, timestamp);
+        let result = validate_r0_response(response, imei, &r0_operation, user_id, timestamp);
 
         assert!(result.is_err());
     }
@@ -129,91 +129,108 @@ mod validate_l1_response_tests {
 
 #[cfg(test)]
 mod validate_s7_response_tests {
-    use crate::server::protocol::validate_s7_response;
+    use crate::{
+        commands::scooter_setting_command::{
+            HeadlightSwitch, ModeSetting, TaillightsFlashing, ThrottleResponse,
+        },
+        server::{protocol::validate_s7_response, registry::ScooterSettings},
+    };
 
     #[test]
     fn test_validate_s7_response_valid() {
         let imei = "123456789123456";
-        let headlight_switch = 0;
-        let mode_setting = 3;
-        let throttle_response = 0;
-        let taillights_flashing = 0;
+        let settings = ScooterSettings::reported(
+            &HeadlightSwitch::NoSet,
+            &ModeSetting::HighSpeed,
+            &ThrottleResponse::NoSet,
+            &TaillightsFlashing::NoSet,
+        );
         let response = format!(
-            "*SCOR,{vendor},{imei},S7,{headlight_switch},{mode_setting},{throttle_response},{taillights_flashing}#\n",
+            "*SCOR,{vendor},{imei},S7,0,3,0,0#\n",
             vendor = crate::config::VENDOR,
-            imei = imei,
-            headlight_switch = headlight_switch,
-            mode_setting = mode_setting,
-            throttle_response = throttle_response,
-            taillights_flashing = taillights_flashing
+            imei = imei
         );
 
-        let result = validate_s7_response(
-            &response,
-            imei,
-            headlight_switch,
-            mode_setting,
-            throttle_response,
-            taillights_flashing,
-        );
+        let result = validate_s7_response(&response, imei, &settings);
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_validate_s7_response_other_settings() {
+        let imei = "123456789123456";
+        let settings = ScooterSettings::reported(
+            &HeadlightSwitch::NoSet,
+            &ModeSetting::LowSpeed,
+            &ThrottleResponse::NoSet,
+            &TaillightsFlashing::NoSet,
+        );
+        let response = format!(
+            "*SCOR,{vendor},{imei},S7,0,3,0,0#\n",
+            vendor = crate::config::VENDOR,
+            imei = imei
+        );
+
+        let result = validate_s7_response(&response, imei, &settings);
+
+        assert!(result.is_err());
+    }
 }
+
 #[cfg(test)]
 mod validate_response_tests {
-    use crate::server::protocol::validate_command;
+    use crate::server::protocol::{validate_l0_response, validate_l1_response};
 
     #[test]
-    fn test_validate_command_valid() {
+    fn test_validate_response_valid() {
         let imei = "123456789123456";
-        let command_type = "CUSTOM";
-        let content = &["content1", "content2"];
         let response = format!(
-            "*SCOR,{vendor},{imei},{command_type},{content1},{content2}#\n",
+            "*SCOR,{vendor},{imei},L1,0,1234,1497689816,3#\n",
             vendor = crate::config::VENDOR,
-            imei = imei,
-            command_type = command_type,
-            content1 = content[0],
-            content2 = content[1]
+            imei = imei
         );
 
-        let result = validate_command(&response, imei, command_type, content);
+        let result = validate_l1_response(&response, imei, 1234);
 
         assert!(result.is_ok());
     }
 
     #[test]
-    fn test_validate_command_invalid_vendor() {
+    fn test_validate_response_invalid_vendor() {
         let imei = "123456789123456";
-        let command_type = "CUSTOM";
-        let content = &["content1", "content2"];
         let response = format!(
-            "*SCOR,INVALID,{imei},{command_type},{content1},{content2}#\n",
-            imei = imei,
-            command_type = command_type,
-            content1 = content[0],
-            content2 = content[1]
+            "*SCOR,INVALID,{imei},L1,0,1234,1497689816,3#\n",
+            imei = imei
         );
 
-        let result = validate_command(&response, imei, command_type, content);
+        let result = validate_l1_response(&response, imei, 1234);
 
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_validate_command_invalid_content() {
+    fn test_validate_response_invalid_content() {
         let imei = "123456789123456";
-        let command_type = "CUSTOM";
-        let content = &["content1", "content2"];
         let response = format!(
-            "*SCOR,{vendor},{imei},{command_type},content3,content4#\n",
+            "*SCOR,{vendor},{imei},L1,content3,content4#\n",
             vendor = crate::config::VENDOR,
-            imei = imei,
-            command_type = command_type
+            imei = imei
+        );
+
+        let result = validate_l1_response(&response, imei, 1234);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_validate_response_other_imei() {
+        let imei = "123456789123456";
+        let response = format!(
+            "*SCOR,{vendor},999999999999999,L0,0,1234,1497689816#\n",
+            vendor = crate::config::VENDOR
         );
 
-        let result = validate_command(&response, imei, command_type, content);
+        let result = validate_l0_response(&response, imei, 1234, 1497689816);
 
         assert!(result.is_err());
     }
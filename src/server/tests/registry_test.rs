@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod registry_tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        commands::{
+            hearbeat_command::{ChargingStatus, ScooterStatus},
+            parser::parse_command,
+            scooter_setting_command::{HeadlightSwitch, ModeSetting},
+        },
+        server::registry::{self, DeviceRegistry},
+    };
+
+    const IMEI: &str = "123456789123456";
+
+    fn record(registry: &DeviceRegistry, frame: &str) {
+        let command = parse_command(frame).unwrap();
+        registry::record(registry, command.imei(), &command, Utc::now());
+    }
+
+    #[test]
+    fn test_heartbeat_updates_battery_and_status() {
+        let registry = DeviceRegistry::default();
+        record(&registry, &format!("*SCOR,LZ,{IMEI},Q0,412,80,28#\n"));
+        record(&registry, &format!("*SCOR,LZ,{IMEI},H0,1,398,20,75,1#\n"));
+
+        let state = registry::get(&registry, IMEI).unwrap();
+        assert_eq!(state.power, Some(75));
+        assert_eq!(state.voltage, Some(3.98));
+        assert_eq!(state.signal, Some(20));
+        assert_eq!(state.status, Some(ScooterStatus::Locked));
+        assert_eq!(state.charging, Some(ChargingStatus::Charging));
+    }
+
+    #[test]
+    fn test_lock_and_unlock_replies_update_status() {
+        let registry = DeviceRegistry::default();
+        record(&registry, &format!("*SCOR,LZ,{IMEI},L0,0,1,1497689816#\n"));
+        assert_eq!(
+            registry::get(&registry, IMEI).unwrap().status,
+            Some(ScooterStatus::Unlocked)
+        );
+
+        // A failed lock leaves the scooter unlocked
+        record(
+            &registry,
+            &format!("*SCOR,LZ,{IMEI},L1,1,1,1497689816,3#\n"),
+        );
+        assert_eq!(
+            registry::get(&registry, IMEI).unwrap().status,
+            Some(ScooterStatus::Unlocked)
+        );
+
+        record(
+            &registry,
+            &format!("*SCOR,LZ,{IMEI},L1,0,1,1497689816,3#\n"),
+        );
+        assert_eq!(
+            registry::get(&registry, IMEI).unwrap().status,
+            Some(ScooterStatus::Locked)
+        );
+    }
+
+    #[test]
+    fn test_positioning_keeps_last_effective_fix() {
+        let registry = DeviceRegistry::default();
+        record(
+            &registry,
+            &format!(
+                "*SCOR,LZ,{IMEI},D0,0,124458.00,A,2237.7514,N,11408.6214,E,6,0.21,151216,10,M,A#\n"
+            ),
+        );
+        record(
+            &registry,
+            &format!(
+                "*SCOR,LZ,{IMEI},D0,0,130000.00,V,0000.0000,N,00000.0000,E,0,0,151216,0,M,N#\n"
+            ),
+        );
+
+        let position = registry::get(&registry, IMEI).unwrap().position.unwrap();
+        assert!((position.latitude - 22.62919).abs() < 1e-5);
+        assert!((position.longitude - 114.14369).abs() < 1e-5);
+        assert_eq!(
+            position.fixed_at,
+            Utc.with_ymd_and_hms(2016, 12, 15, 12, 44, 58).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_settings_ignore_dont_set_values() {
+        let registry = DeviceRegistry::default();
+        record(&registry, &format!("*SCOR,LZ,{IMEI},S7,2,1,0,0#\n"));
+        record(&registry, &format!("*SCOR,LZ,{IMEI},S7,0,3,0,0#\n"));
+
+        let settings = registry::get(&registry, IMEI).unwrap().settings;
+        assert_eq!(settings.headlight_switch, Some(HeadlightSwitch::Open));
+        assert_eq!(settings.mode_setting, Some(ModeSetting::HighSpeed));
+        assert_eq!(settings.throttle_response, None);
+    }
+
+    #[test]
+    fn test_status_report_updates_speed_and_mileage() {
+        let registry = DeviceRegistry::default();
+        record(
+            &registry,
+            &format!("*SCOR,LZ,{IMEI},S6,64,2,18,0,372,0,0,25,150#\n"),
+        );
+        // Older firmware leaves out the mileage
+        record(
+            &registry,
+            &format!("*SCOR,LZ,{IMEI},S6,63,2,21,0,371,0,0,25#\n"),
+        );
+
+        let state = registry::get(&registry, IMEI).unwrap();
+        assert_eq!(state.power, Some(63));
+        assert_eq!(state.speed, Some(21));
+        assert_eq!(state.ride_mileage, Some(1500));
+        assert_eq!(state.status, Some(ScooterStatus::Unlocked));
+        assert_eq!(state.settings.mode_setting, Some(ModeSetting::MediumSpeed));
+    }
+
+    #[test]
+    fn test_settings_are_forgotten_after_restart_or_unlock() {
+        let registry = DeviceRegistry::default();
+        record(&registry, &format!("*SCOR,LZ,{IMEI},S7,2,1,0,0#\n"));
+        record(&registry, &format!("*SCOR,LZ,{IMEI},L0,0,1,1497689816#\n"));
+        assert!(registry::get(&registry, IMEI).unwrap().settings.is_empty());
+
+        record(&registry, &format!("*SCOR,LZ,{IMEI},S7,2,1,0,0#\n"));
+        record(&registry, &format!("*SCOR,LZ,{IMEI},Q0,412,80,28#\n"));
+        assert!(registry::get(&registry, IMEI).unwrap().settings.is_empty());
+    }
+
+    #[test]
+    fn test_frames_without_state_do_not_create_entries() {
+        let registry = DeviceRegistry::default();
+        record(&registry, &format!("*SCOR,LZ,{IMEI},W0,1#\n"));
+
+        assert!(registry::get(&registry, IMEI).is_none());
+    }
+}
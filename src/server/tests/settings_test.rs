@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod settings_tests {
+    use std::time::Duration;
+
+    use axum::{
+        extract::{Path, State},
+        http::StatusCode,
+        Json,
+    };
+    use serde_json::{json, Value};
+    use tokio::{io::AsyncWriteExt, time::timeout};
+
+    use crate::server::{
+        events::DeviceEvent,
+        settings_handler::{settings_handler, SettingsRequest},
+        tests::support::{body, read_command, sign_in, IMEI},
+        AppState,
+    };
+
+    async fn put_settings(
+        state: &AppState,
+        payload: Value,
+    ) -> tokio::task::JoinHandle<(StatusCode, Value)> {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let request: SettingsRequest = serde_json::from_value(payload).unwrap();
+            body(settings_handler(State(state), Path(IMEI.to_string()), Json(request)).await).await
+        })
+    }
+
+    #[tokio::test]
+    async fn test_settings_are_sent_in_one_s7_and_recorded() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        let request = put_settings(
+            &state,
+            json!({"throttle_response": "on", "taillights_flashing": "off"}),
+        )
+        .await;
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},S7,0,0,2,1#\n")
+        );
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,0,0,2,1#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["settings"],
+            json!({
+                "headlight_switch": null,
+                "mode_setting": null,
+                "throttle_response": "open",
+                "taillights_flashing": "shutdown",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_echo_is_not_accepted() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        let request = put_settings(&state, json!({"speed_mode": "high"})).await;
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},S7,0,3,0,0#\n")
+        );
+        let mut events = state.events.subscribe();
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,0,1,0,0#\n").as_bytes())
+            .await
+            .unwrap();
+        // Frames are published once the waiting requests have seen them
+        loop {
+            let event = timeout(Duration::from_secs(1), events.recv())
+                .await
+                .unwrap();
+            if let Ok(DeviceEvent::Frame { command, .. }) = event {
+                if command.code() == "S7" {
+                    break;
+                }
+            }
+        }
+        assert!(!request.is_finished());
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S7,0,3,0,0#\n").as_bytes())
+            .await
+            .unwrap();
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["settings"]["mode_setting"], "high_speed");
+    }
+
+    #[tokio::test]
+    async fn test_empty_request_is_rejected() {
+        let state = AppState::new();
+
+        let (status, body) = put_settings(&state, json!({})).await.await.unwrap();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+    }
+}
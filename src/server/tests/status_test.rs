@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod status_tests {
+    use std::time::Duration;
+
+    use axum::{extract::State, http::StatusCode, Json};
+    use tokio::{io::AsyncWriteExt, time::timeout};
+
+    use crate::server::{
+        registry,
+        status_handler::{status_handler, StatusRequest},
+        status_poll,
+        tests::support::{body, read_command, read_until, sign_in, IMEI},
+        AppState,
+    };
+
+    #[tokio::test]
+    async fn test_query_status_returns_report() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        let clients = state.clients.clone();
+        let request = tokio::spawn(async move {
+            let request = Json(StatusRequest {
+                imei: IMEI.to_string(),
+            });
+            body(status_handler(State(clients), request).await).await
+        });
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},S6#\n")
+        );
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},S6,80,3,22,0,372,0,0,28,42#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["report"]["speed"], 22);
+        assert_eq!(body["report"]["ride_mileage"], 420);
+        assert_eq!(body["report"]["mode_setting"], "high_speed");
+        assert_eq!(
+            registry::get(&state.registry, IMEI).unwrap().ride_mileage,
+            Some(420)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_poll_only_queries_riding_scooters() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        // Unknown lock state counts as parked
+        assert!(status_poll::poll(&state).await.is_empty());
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},H0,0,412,28,80,0#\n").as_bytes())
+            .await
+            .unwrap();
+        read_until(&mut device, &format!("*SCOS,LZ,{IMEI},H0#\n")).await;
+
+        assert_eq!(status_poll::poll(&state).await, vec![IMEI.to_string()]);
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},S6#\n")
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Helpers for tests that drive a connection through a fake scooter socket.
+
+use std::time::Duration;
+
+use axum::{http::StatusCode, response::IntoResponse};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+use crate::server::{handler::handle_connection, AppState};
+
+pub const IMEI: &str = "123456789123456";
+
+/// Status and JSON body of a handler's response.
+pub async fn body(response: impl IntoResponse) -> (StatusCode, Value) {
+    let response = response.into_response();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+pub async fn sign_in(state: &AppState) -> TcpStream {
+    let mut events = state.events.subscribe();
+    let mut device = connect(state).await;
+    device
+        .write_all(format!("*SCOR,LZ,{IMEI},Q0,412,80,28#\n").as_bytes())
+        .await
+        .unwrap();
+    events.recv().await.unwrap();
+    assert_eq!(
+        read_command(&mut device).await,
+        format!("0xFFFF*SCOS,LZ,{IMEI},Q0#\n")
+    );
+    device
+}
+
+/// Reads one command sent by the server, including its reserved header.
+pub async fn read_command(device: &mut TcpStream) -> String {
+    let mut buffer = [0; 1024];
+    let n = timeout(Duration::from_secs(1), device.read(&mut buffer))
+        .await
+        .unwrap()
+        .unwrap();
+    String::from_utf8_lossy(&buffer[..n]).to_string()
+}
+
+/// Reads commands from the server until one contains `needle`.
+pub async fn read_until(device: &mut TcpStream, needle: &str) -> String {
+    let mut received = String::new();
+    while !received.contains(needle) {
+        received.push_str(&read_command(device).await);
+    }
+    received
+}
+
+pub async fn connect(state: &AppState) -> TcpStream {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let state = state.clone();
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let _ = handle_connection(socket, state).await;
+    });
+    TcpStream::connect(address).await.unwrap()
+}
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tracking_tests {
+    use std::time::Duration;
+
+    use axum::{
+        extract::{Path, State},
+        http::StatusCode,
+        response::IntoResponse,
+        Json,
+    };
+    use chrono::Utc;
+    use tokio::{io::AsyncWriteExt, time::timeout};
+
+    use crate::{
+        commands::hearbeat_command::ScooterStatus,
+        server::{
+            presence, registry,
+            tests::support::{body, connect, read_command, read_until, sign_in, IMEI},
+            tracking::TrackingPolicy,
+            tracking_handler::{tracking_handler, TrackingRequest},
+            AppState,
+        },
+    };
+
+    #[test]
+    fn test_policy_falls_back_to_defaults() {
+        let defaults = TrackingPolicy {
+            locked_secs: Some(300),
+            riding_secs: Some(10),
+        };
+        let policy = TrackingPolicy {
+            locked_secs: None,
+            riding_secs: Some(5),
+        }
+        .or(defaults);
+
+        assert_eq!(policy.interval(&ScooterStatus::Locked), Some(300));
+        assert_eq!(policy.interval(&ScooterStatus::Unlocked), Some(5));
+        assert_eq!(
+            TrackingPolicy::default().interval(&ScooterStatus::Locked),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interval_follows_lock_state() {
+        let mut state = AppState::new();
+        state.tracking_policy = TrackingPolicy {
+            locked_secs: Some(300),
+            riding_secs: Some(10),
+        };
+        let mut device = connect(&state).await;
+
+        // Unknown scooters are assumed to be parked when they sign in
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},Q0,412,80,28#\n").as_bytes())
+            .await
+            .unwrap();
+        read_until(&mut device, &format!("*SCOS,LZ,{IMEI},D1,300#\n")).await;
+
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},L0,0,1,1497689816#\n").as_bytes())
+            .await
+            .unwrap();
+        read_until(&mut device, &format!("*SCOS,LZ,{IMEI},D1,10#\n")).await;
+
+        // A heartbeat that does not change the lock state sends nothing
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},H0,0,412,28,80,0#\n").as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},H0#\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_applies_interval_for_current_state() {
+        let state = AppState::new();
+        let mut device = sign_in(&state).await;
+
+        let request_state = state.clone();
+        let request = tokio::spawn(async move {
+            let payload = Json(TrackingRequest {
+                locked_secs: Some(120),
+                riding_secs: Some(15),
+            });
+            body(tracking_handler(State(request_state), Path(IMEI.to_string()), payload).await)
+                .await
+        });
+
+        assert_eq!(
+            read_command(&mut device).await,
+            format!("0xFFFF*SCOS,LZ,{IMEI},D1,120#\n")
+        );
+        device
+            .write_all(format!("*SCOR,LZ,{IMEI},D1,120#\n").as_bytes())
+            .await
+            .unwrap();
+
+        let (status, body) = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["applied_secs"], 120);
+        assert_eq!(body["policy"]["riding_secs"], 15);
+        assert_eq!(
+            registry::get(&state.registry, IMEI)
+                .unwrap()
+                .tracking_interval,
+            Some(120)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_stores_policy_for_offline_scooter() {
+        let state = AppState::new();
+        presence::record_seen(&state.presence, IMEI, Utc::now());
+        presence::record_disconnected(&state.presence, IMEI);
+        let payload = Json(TrackingRequest {
+            locked_secs: None,
+            riding_secs: Some(15),
+        });
+
+        let (status, body) =
+            body(tracking_handler(State(state.clone()), Path(IMEI.to_string()), payload).await)
+                .await;
+
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body["success"], true);
+        assert_eq!(state.tracking.lock().unwrap()[IMEI].riding_secs, Some(15));
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_rejects_unknown_scooter() {
+        let state = AppState::new();
+        let payload = Json(TrackingRequest {
+            locked_secs: Some(120),
+            riding_secs: None,
+        });
+
+        let response = tracking_handler(State(state.clone()), Path(IMEI.to_string()), payload)
+            .await
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(state.tracking.lock().unwrap().is_empty());
+    }
+}
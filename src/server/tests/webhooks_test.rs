@@ -0,0 +1,299 @@
+#[cfg(test)]
+mod webhooks_tests {
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use axum::{
+        body::Bytes,
+        extract::{Path, State},
+        http::{HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing::post,
+        Json, Router,
+    };
+    use chrono::Utc;
+    use serde_json::{json, Value};
+    use tokio::{net::TcpListener, sync::mpsc, time::timeout};
+
+    use crate::{
+        commands::{alarm_command::AlarmType, parser::parse_command},
+        server::{
+            alarms::{Alarm, Severity},
+            events::{self, DeviceEvent},
+            presence::Connectivity,
+            tests::support::{body, IMEI},
+            webhooks::{
+                self, DeadLetters, RetryPolicy, Webhook, WebhookEventType, WebhookPayload,
+                EVENT_TYPE_HEADER, SIGNATURE_HEADER,
+            },
+            webhooks_handler::{
+                create_webhook_handler, delete_webhook_handler, CreateWebhookRequest,
+            },
+            AppState,
+        },
+    };
+
+    const SECRET: &str = "s3cret";
+    const FAST_RETRY: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(10),
+    };
+
+    /// A local HTTP stand-in for a webhook receiver. Answers with the queued statuses, then 200.
+    struct Receiver {
+        url: String,
+        requests: mpsc::UnboundedReceiver<(HeaderMap, Bytes)>,
+    }
+
+    impl Receiver {
+        async fn start(statuses: &[StatusCode]) -> Self {
+            let statuses = Arc::new(Mutex::new(VecDeque::from(statuses.to_vec())));
+            let (sender, requests) = mpsc::unbounded_channel();
+            let app = Router::new().route(
+                "/hook",
+                post(move |headers: HeaderMap, body: Bytes| {
+                    let statuses = statuses.clone();
+                    let sender = sender.clone();
+                    async move {
+                        let _ = sender.send((headers, body));
+                        let status = statuses.lock().unwrap().pop_front();
+                        status.unwrap_or(StatusCode::OK)
+                    }
+                }),
+            );
+
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/hook", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await });
+            Self { url, requests }
+        }
+
+        async fn next(&mut self) -> (HeaderMap, Bytes) {
+            timeout(Duration::from_secs(2), self.requests.recv())
+                .await
+                .unwrap()
+                .unwrap()
+        }
+    }
+
+    fn webhook(url: &str, events: Vec<WebhookEventType>) -> Webhook {
+        Webhook {
+            id: "hook".to_string(),
+            url: url.to_string(),
+            secret: SECRET.to_string(),
+            events,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn alarm_event() -> DeviceEvent {
+        let now = Utc::now();
+        DeviceEvent::Alarm {
+            imei: IMEI.to_string(),
+            alarm: Alarm {
+                alarm_type: AlarmType::IllegalRemoval,
+                severity: Severity::Critical,
+                raised_at: now,
+                last_seen_at: now,
+                repeats: 0,
+            },
+        }
+    }
+
+    fn alarm_payload() -> WebhookPayload {
+        WebhookPayload {
+            event_type: WebhookEventType::Alarm,
+            occurred_at: Utc::now(),
+            event: alarm_event(),
+        }
+    }
+
+    #[test]
+    fn test_event_types_of_device_events() {
+        let sign_in = DeviceEvent::Frame {
+            imei: IMEI.to_string(),
+            command: parse_command(&format!("*SCOR,LZ,{IMEI},Q0,412,80,28#\n")).unwrap(),
+        };
+        let no_fix = DeviceEvent::Frame {
+            imei: IMEI.to_string(),
+            command: parse_command(&format!(
+                "*SCOR,LZ,{IMEI},D0,1,130000.00,V,0000.0000,N,00000.0000,E,0,0,151216,0,M,N#\n"
+            ))
+            .unwrap(),
+        };
+        let connectivity = DeviceEvent::ConnectivityChanged {
+            imei: IMEI.to_string(),
+            from: Connectivity::Online,
+            to: Connectivity::Offline,
+        };
+
+        assert_eq!(
+            WebhookEventType::of(&sign_in),
+            Some(WebhookEventType::SignIn)
+        );
+        assert_eq!(
+            WebhookEventType::of(&alarm_event()),
+            Some(WebhookEventType::Alarm)
+        );
+        assert_eq!(WebhookEventType::of(&no_fix), None);
+        assert_eq!(WebhookEventType::of(&connectivity), None);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(FAST_RETRY.delay(1), Duration::from_millis(10));
+        assert_eq!(FAST_RETRY.delay(3), Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_subscribed_events_are_delivered_signed() {
+        let mut receiver = Receiver::start(&[]).await;
+        let state = AppState::new();
+        let hook = webhook(&receiver.url, vec![WebhookEventType::Alarm]);
+        state.webhooks.lock().unwrap().insert(hook.id.clone(), hook);
+
+        tokio::spawn(webhooks::run(state.clone()));
+        while state.events.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        events::publish(
+            &state.events,
+            DeviceEvent::Disconnected {
+                imei: IMEI.to_string(),
+                reason: events::DisconnectReason::Closed,
+            },
+        );
+        events::publish(&state.events, alarm_event());
+
+        let (headers, body) = receiver.next().await;
+        assert_eq!(headers[EVENT_TYPE_HEADER], "alarm");
+        assert_eq!(
+            headers[SIGNATURE_HEADER].to_str().unwrap(),
+            format!("sha256={}", webhooks::sign(SECRET, &body))
+        );
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["type"], "alarm");
+        assert_eq!(body["event"]["alarm"]["severity"], "critical");
+
+        // The disconnect was filtered out
+        assert!(
+            timeout(Duration::from_millis(200), receiver.requests.recv())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_are_delivered_in_order_across_retries() {
+        let mut receiver = Receiver::start(&[StatusCode::INTERNAL_SERVER_ERROR]).await;
+        let mut state = AppState::new();
+        state.webhook_retry = FAST_RETRY;
+        let hook = webhook(&receiver.url, vec![]);
+        state.webhooks.lock().unwrap().insert(hook.id.clone(), hook);
+
+        tokio::spawn(webhooks::run(state.clone()));
+        while state.events.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        events::publish(&state.events, alarm_event());
+        events::publish(
+            &state.events,
+            DeviceEvent::Unlocked {
+                imei: IMEI.to_string(),
+            },
+        );
+
+        let mut types = Vec::new();
+        for _ in 0..3 {
+            let (headers, _) = receiver.next().await;
+            types.push(headers[EVENT_TYPE_HEADER].to_str().unwrap().to_string());
+        }
+        // The unlock waits for the alarm's retry
+        assert_eq!(types, ["alarm", "alarm", "unlocked"]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_retried() {
+        let mut receiver = Receiver::start(&[
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ])
+        .await;
+        let dead_letters = DeadLetters::default();
+
+        webhooks::deliver(
+            reqwest::Client::new(),
+            webhook(&receiver.url, vec![]),
+            alarm_payload(),
+            FAST_RETRY,
+            dead_letters.clone(),
+        )
+        .await;
+
+        let first = receiver.next().await.1;
+        for _ in 0..2 {
+            assert_eq!(receiver.next().await.1, first);
+        }
+        assert!(dead_letters.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_delivery_is_dead_lettered() {
+        let receiver = Receiver::start(&[StatusCode::INTERNAL_SERVER_ERROR; 3]).await;
+        let dead_letters = DeadLetters::default();
+
+        webhooks::deliver(
+            reqwest::Client::new(),
+            webhook(&receiver.url, vec![]),
+            alarm_payload(),
+            FAST_RETRY,
+            dead_letters.clone(),
+        )
+        .await;
+
+        let dead_letters = dead_letters.lock().unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].attempts, 3);
+        assert!(dead_letters[0].last_error.contains("500"));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_registration() {
+        let state = AppState::new();
+
+        let invalid = CreateWebhookRequest {
+            url: "ftp://example.com".to_string(),
+            secret: SECRET.to_string(),
+            events: vec![],
+        };
+        let response = create_webhook_handler(State(state.clone()), Json(invalid))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let request: CreateWebhookRequest = serde_json::from_value(json!({
+            "url": "https://example.com/hook",
+            "secret": SECRET,
+            "events": ["alarm", "locked"],
+        }))
+        .unwrap();
+        let (status, body) =
+            body(create_webhook_handler(State(state.clone()), Json(request)).await).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(body["webhook"].get("secret").is_none());
+        assert_eq!(body["webhook"]["events"], json!(["alarm", "locked"]));
+
+        let id = body["webhook"]["id"].as_str().unwrap().to_string();
+        let response = delete_webhook_handler(State(state.clone()), Path(id.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = delete_webhook_handler(State(state), Path(id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
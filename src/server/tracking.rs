@@ -0,0 +1,77 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::hearbeat_command::ScooterStatus;
+
+use super::{
+    commands,
+    handler::{send_command, Connection},
+    registry, AppState,
+};
+
+/// D1 tracking intervals in seconds for each lock state. `None` leaves the scooter's current
+/// interval untouched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrackingPolicy {
+    pub locked_secs: Option<u16>,
+    pub riding_secs: Option<u16>,
+}
+
+impl TrackingPolicy {
+    /// The defaults from `TRACKING_LOCKED_SECS` and `TRACKING_RIDING_SECS`.
+    pub fn from_env() -> Self {
+        Self {
+            locked_secs: crate::config::tracking_locked_interval(),
+            riding_secs: crate::config::tracking_riding_interval(),
+        }
+    }
+
+    pub fn interval(&self, status: &ScooterStatus) -> Option<u16> {
+        match status {
+            ScooterStatus::Locked => self.locked_secs,
+            ScooterStatus::Unlocked => self.riding_secs,
+        }
+    }
+
+    /// Fills the intervals this policy leaves unset from `defaults`.
+    pub fn or(self, defaults: TrackingPolicy) -> Self {
+        Self {
+            locked_secs: self.locked_secs.or(defaults.locked_secs),
+            riding_secs: self.riding_secs.or(defaults.riding_secs),
+        }
+    }
+}
+
+/// Per-scooter policies set through the API, taking precedence over the defaults.
+pub type TrackingOverrides = Arc<Mutex<HashMap<String, TrackingPolicy>>>;
+
+/// The policy in effect for `imei`.
+pub fn policy(state: &AppState, imei: &str) -> TrackingPolicy {
+    let overrides = state.tracking.lock().unwrap().get(imei).copied();
+    overrides.unwrap_or_default().or(state.tracking_policy)
+}
+
+/// The scooter's lock status, assuming it is parked until it reports otherwise.
+pub fn current_status(state: &AppState, imei: &str) -> ScooterStatus {
+    registry::get(&state.registry, imei)
+        .and_then(|device| device.status)
+        .unwrap_or(ScooterStatus::Locked)
+}
+
+/// Sends the interval for `status` without waiting for the D1 reply, which arrives through the
+/// connection's reader like any other frame.
+pub async fn apply(state: &AppState, imei: &str, connection: &Connection, status: &ScooterStatus) {
+    let Some(interval) = policy(state, imei).interval(status) else {
+        return;
+    };
+
+    let command = commands::generate_d1_command(imei, interval);
+    match send_command(connection, &command).await {
+        Ok(()) => println!("Set tracking interval of {} to {}s", imei, interval),
+        Err(err) => println!("Failed to set tracking interval of {}: {}", imei, err),
+    }
+}
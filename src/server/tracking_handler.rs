@@ -0,0 +1,120 @@
+use crate::server::{
+    commands,
+    handler::*,
+    presence,
+    tracking::{self, TrackingPolicy},
+    AppState,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+
+/// Intervals in seconds; 0 turns tracking off. Omitted fields keep their current value.
+#[derive(Deserialize)]
+pub struct TrackingRequest {
+    pub locked_secs: Option<u16>,
+    pub riding_secs: Option<u16>,
+}
+
+#[derive(Serialize)]
+pub struct TrackingResponse {
+    pub success: bool,
+    pub message: String,
+    pub imei: String,
+    pub policy: TrackingPolicy,
+    /// The interval sent to the scooter for its current lock state.
+    pub applied_secs: Option<u16>,
+}
+
+pub async fn tracking_handler(
+    State(state): State<AppState>,
+    Path(imei): Path<String>,
+    Json(payload): Json<TrackingRequest>,
+) -> impl IntoResponse {
+    // Overrides are only kept for scooters that exist
+    if !presence::is_known(&state.presence, &imei) {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(TrackingResponse {
+                success: false,
+                message: format!("Scooter with IMEI {} has never connected", imei),
+                imei,
+                policy: state.tracking_policy,
+                applied_secs: None,
+            }),
+        );
+    }
+
+    let policy = {
+        let mut overrides = state.tracking.lock().unwrap();
+        let entry = overrides.entry(imei.clone()).or_default();
+        if payload.locked_secs.is_some() {
+            entry.locked_secs = payload.locked_secs;
+        }
+        if payload.riding_secs.is_some() {
+            entry.riding_secs = payload.riding_secs;
+        }
+        entry.or(state.tracking_policy)
+    };
+
+    let connection = match get_client(&state.clients, &imei).await {
+        Ok(connection) => connection,
+        Err(_) => {
+            return (
+                StatusCode::ACCEPTED,
+                Json(TrackingResponse {
+                    success: true,
+                    message: format!(
+                        "Scooter with IMEI {} is offline; the policy applies when it reconnects",
+                        imei
+                    ),
+                    imei,
+                    policy,
+                    applied_secs: None,
+                }),
+            );
+        }
+    };
+
+    let status = tracking::current_status(&state, &imei);
+    let Some(interval) = policy.interval(&status) else {
+        return (
+            StatusCode::OK,
+            Json(TrackingResponse {
+                success: true,
+                message: format!("No interval set for {:?} scooters", status),
+                imei,
+                policy,
+                applied_secs: None,
+            }),
+        );
+    };
+
+    let d1_command = commands::generate_d1_command(&imei, interval);
+    if let Err(err) = request_d1(&connection, &d1_command, &imei, interval).await {
+        return (
+            err.status_code(),
+            Json(TrackingResponse {
+                success: false,
+                message: err.to_string(),
+                imei,
+                policy,
+                applied_secs: None,
+            }),
+        );
+    }
+
+    (
+        StatusCode::OK,
+        Json(TrackingResponse {
+            success: true,
+            message: "Tracking interval updated".to_string(),
+            imei,
+            policy,
+            applied_secs: Some(interval),
+        }),
+    )
+}
@@ -1,4 +1,5 @@
 use crate::{
+    commands::unlock_command::R0Operation,
     config::USER_ID,
     server::ClientMap,
     server::{commands, handler::*},
@@ -28,17 +29,17 @@ pub async fn unlock_handler(
     Json(payload): Json<UnlockRequest>,
 ) -> impl IntoResponse {
     let imei = payload.imei.clone();
-    let r0_operation = commands::R0Operation::Unlock;
+    let r0_operation = R0Operation::Unlock;
     let r0_timestamp = timestamp::current();
 
-    let mut socket = match get_client_socket(&clients, &imei).await {
-        Ok(socket) => socket,
+    let connection = match get_client(&clients, &imei).await {
+        Ok(connection) => connection,
         Err(err) => {
             return (
-                StatusCode::NOT_FOUND,
+                err.status_code(),
                 Json(UnlockResponse {
                     success: false,
-                    message: err,
+                    message: err.to_string(),
                     imei,
                 }),
             );
@@ -46,62 +47,29 @@ pub async fn unlock_handler(
     };
 
     let r0_command = commands::generate_r0_command(&imei, &r0_operation, 20, USER_ID, r0_timestamp);
-    if let Err(err) = send_command(&mut socket, &r0_command).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(UnlockResponse {
-                success: false,
-                message: err,
-                imei,
-            }),
-        );
-    }
-
-    let r0_key = match handle_r0_response(&mut socket, &imei, &r0_operation, r0_timestamp).await {
-        Ok(key) => key,
-        Err(err) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(UnlockResponse {
-                    success: false,
-                    message: err,
-                    imei,
-                }),
-            );
-        }
-    };
+    let r0_key =
+        match request_r0(&connection, &r0_command, &imei, &r0_operation, r0_timestamp).await {
+            Ok(key) => key,
+            Err(err) => {
+                return (
+                    err.status_code(),
+                    Json(UnlockResponse {
+                        success: false,
+                        message: err.to_string(),
+                        imei,
+                    }),
+                );
+            }
+        };
 
     let l0_timestamp = timestamp::current();
     let l0_command = commands::generate_l0_command(&imei, &r0_key, USER_ID, l0_timestamp);
-    if let Err(err) = send_command(&mut socket, &l0_command).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(UnlockResponse {
-                success: false,
-                message: err,
-                imei,
-            }),
-        );
-    }
-
-    if let Err(err) = handle_l_response(&mut socket, &imei, "L0", Some(l0_timestamp)).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(UnlockResponse {
-                success: false,
-                message: err,
-                imei,
-            }),
-        );
-    }
-
-    let final_ack = commands::generate_l0_ack(&imei);
-    if let Err(err) = send_command(&mut socket, &final_ack).await {
+    if let Err(err) = request_l(&connection, &l0_command, &imei, "L0", Some(l0_timestamp)).await {
         return (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            err.status_code(),
             Json(UnlockResponse {
                 success: false,
-                message: err,
+                message: err.to_string(),
                 imei,
             }),
         );
@@ -0,0 +1,302 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+
+use crate::commands::{positioning_command::PositioningStatus, scooter_command::ScooterCommand};
+
+use super::{events::DeviceEvent, AppState};
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body keyed with the webhook secret>`.
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+pub const EVENT_TYPE_HEADER: &str = "X-Event-Type";
+
+/// The device events a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventType {
+    SignIn,
+    Heartbeat,
+    Alarm,
+    Position,
+    Unlocked,
+    Locked,
+    Disconnected,
+}
+
+impl WebhookEventType {
+    /// The webhook type of `event`, or `None` if it is not delivered to webhooks.
+    pub fn of(event: &DeviceEvent) -> Option<Self> {
+        match event {
+            DeviceEvent::Frame { command, .. } => match command {
+                ScooterCommand::SigningIn { .. } => Some(WebhookEventType::SignIn),
+                ScooterCommand::HeartBeat { .. } => Some(WebhookEventType::Heartbeat),
+                ScooterCommand::PositioningResponse(response)
+                    if response.positioning_status == PositioningStatus::Effective =>
+                {
+                    Some(WebhookEventType::Position)
+                }
+                _ => None,
+            },
+            DeviceEvent::Alarm { .. } => Some(WebhookEventType::Alarm),
+            DeviceEvent::Unlocked { .. } => Some(WebhookEventType::Unlocked),
+            DeviceEvent::Locked { .. } => Some(WebhookEventType::Locked),
+            DeviceEvent::Disconnected { .. } => Some(WebhookEventType::Disconnected),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Key for the body signature. Never returned by the API.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Event types to deliver; empty means all of them.
+    pub events: Vec<WebhookEventType>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    pub fn wants(&self, event_type: WebhookEventType) -> bool {
+        self.events.is_empty() || self.events.contains(&event_type)
+    }
+}
+
+/// Registered webhooks by id.
+pub type WebhookMap = Arc<Mutex<HashMap<String, Webhook>>>;
+
+/// The JSON body posted to a webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    #[serde(rename = "type")]
+    pub event_type: WebhookEventType,
+    pub occurred_at: DateTime<Utc>, // RFC 3339
+    pub event: DeviceEvent,
+}
+
+/// A delivery that still failed after the last retry, or was never attempted because the
+/// webhook's queue was full.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetter {
+    pub webhook_id: String,
+    pub url: String,
+    pub payload: WebhookPayload,
+    pub attempts: u32,
+    pub last_error: String,
+    pub failed_at: DateTime<Utc>, // RFC 3339
+}
+
+/// Most recent dead letters, oldest first.
+pub type DeadLetters = Arc<Mutex<VecDeque<DeadLetter>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts per delivery, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry; each further retry waits twice as long.
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Read from `WEBHOOK_MAX_ATTEMPTS` and `WEBHOOK_RETRY_BASE_MS`.
+    pub fn from_env() -> Self {
+        Self {
+            max_attempts: crate::config::webhook_max_attempts(),
+            base_delay: crate::config::webhook_retry_base_delay(),
+        }
+    }
+
+    /// How long to wait after failed attempt number `attempt`, counting from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+/// Hex HMAC-SHA256 of `body`, as sent in the signature header after `sha256=`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// A payload queued for a webhook's worker, with the webhook as it was when the event arrived.
+type Delivery = (Webhook, WebhookPayload);
+
+/// Forwards device events to the webhooks subscribed to them until the event channel closes.
+///
+/// Each webhook has its own worker and queue of `WEBHOOK_QUEUE_SIZE` payloads, so a slow
+/// receiver does not hold up the others and gets its events in order. Payloads that do not fit
+/// in a full queue are dead-lettered right away.
+pub async fn run(state: AppState) {
+    let mut events = state.events.subscribe();
+    let client = reqwest::Client::builder()
+        .timeout(crate::config::WEBHOOK_REQUEST_TIMEOUT)
+        .build()
+        .expect("HTTP client configuration is valid");
+    let mut workers: HashMap<String, mpsc::Sender<Delivery>> = HashMap::new();
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                eprintln!(
+                    "Webhooks fell behind, {} events were not delivered",
+                    skipped
+                );
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        let Some(event_type) = WebhookEventType::of(&event) else {
+            continue;
+        };
+        let payload = WebhookPayload {
+            event_type,
+            occurred_at: Utc::now(),
+            event,
+        };
+
+        let subscribers: Vec<Webhook> = {
+            let webhooks = state.webhooks.lock().unwrap();
+            // Workers of deleted webhooks stop once their queue is drained
+            workers.retain(|id, _| webhooks.contains_key(id));
+            webhooks
+                .values()
+                .filter(|webhook| webhook.wants(event_type))
+                .cloned()
+                .collect()
+        };
+        for webhook in subscribers {
+            let queue = workers.entry(webhook.id.clone()).or_insert_with(|| {
+                let (queue, deliveries) = mpsc::channel(crate::config::WEBHOOK_QUEUE_SIZE);
+                tokio::spawn(work(
+                    client.clone(),
+                    deliveries,
+                    state.webhook_retry,
+                    state.dead_letters.clone(),
+                ));
+                queue
+            });
+            if let Err(err) = queue.try_send((webhook, payload.clone())) {
+                let (webhook, payload) = err.into_inner();
+                eprintln!(
+                    "Webhook {} queue is full, dead-lettering an event",
+                    webhook.id
+                );
+                dead_letter(
+                    &state.dead_letters,
+                    DeadLetter {
+                        webhook_id: webhook.id,
+                        url: webhook.url,
+                        payload,
+                        attempts: 0,
+                        last_error: "Delivery queue is full".to_string(),
+                        failed_at: Utc::now(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Delivers a webhook's queued payloads one at a time, in the order they were queued.
+async fn work(
+    client: reqwest::Client,
+    mut deliveries: mpsc::Receiver<Delivery>,
+    retry: RetryPolicy,
+    dead_letters: DeadLetters,
+) {
+    while let Some((webhook, payload)) = deliveries.recv().await {
+        deliver(
+            client.clone(),
+            webhook,
+            payload,
+            retry,
+            dead_letters.clone(),
+        )
+        .await;
+    }
+}
+
+/// Posts `payload` to `webhook`, retrying with exponential backoff and dead-lettering it once
+/// the attempts run out.
+pub async fn deliver(
+    client: reqwest::Client,
+    webhook: Webhook,
+    payload: WebhookPayload,
+    retry: RetryPolicy,
+    dead_letters: DeadLetters,
+) {
+    let body = serde_json::to_vec(&payload).expect("device events serialize to JSON");
+    let signature = format!("sha256={}", sign(&webhook.secret, &body));
+    let event_type = serde_json::to_value(payload.event_type)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    let mut attempt = 0;
+    let last_error = loop {
+        attempt += 1;
+        let response = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .header(EVENT_TYPE_HEADER, &event_type)
+            .body(body.clone())
+            .send()
+            .await;
+
+        let error = match response {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => format!("Receiver answered {}", response.status()),
+            Err(err) => err.to_string(),
+        };
+        if attempt >= retry.max_attempts {
+            break error;
+        }
+
+        println!(
+            "Webhook {} delivery attempt {} failed: {}",
+            webhook.id, attempt, error
+        );
+        tokio::time::sleep(retry.delay(attempt)).await;
+    };
+
+    eprintln!(
+        "Giving up on webhook {} after {} attempts: {}",
+        webhook.id, attempt, last_error
+    );
+    dead_letter(
+        &dead_letters,
+        DeadLetter {
+            webhook_id: webhook.id,
+            url: webhook.url,
+            payload,
+            attempts: attempt,
+            last_error,
+            failed_at: Utc::now(),
+        },
+    );
+}
+
+/// Keeps `letter`, dropping the oldest ones beyond `DEAD_LETTER_CAPACITY`.
+fn dead_letter(dead_letters: &DeadLetters, letter: DeadLetter) {
+    let mut dead_letters = dead_letters.lock().unwrap();
+    dead_letters.push_back(letter);
+    while dead_letters.len() > crate::config::DEAD_LETTER_CAPACITY {
+        dead_letters.pop_front();
+    }
+}
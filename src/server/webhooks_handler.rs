@@ -0,0 +1,128 @@
+use crate::server::{
+    webhooks::{DeadLetter, Webhook, WebhookEventType},
+    AppState,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Shared key for the `X-Signature` body signature.
+    pub secret: String,
+    /// Event types to deliver; omitted or empty means all of them.
+    #[serde(default)]
+    pub events: Vec<WebhookEventType>,
+}
+
+#[derive(Serialize)]
+pub struct WebhookResponse {
+    pub success: bool,
+    pub message: String,
+    pub webhook: Option<Webhook>,
+}
+
+#[derive(Serialize)]
+pub struct WebhooksResponse {
+    pub success: bool,
+    pub message: String,
+    pub webhooks: Vec<Webhook>,
+}
+
+#[derive(Serialize)]
+pub struct DeadLettersResponse {
+    pub success: bool,
+    pub message: String,
+    pub dead_letters: Vec<DeadLetter>,
+}
+
+pub async fn create_webhook_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> impl IntoResponse {
+    let valid_url =
+        reqwest::Url::parse(&payload.url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+    if !valid_url || payload.secret.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(WebhookResponse {
+                success: false,
+                message: "A webhook needs an http(s) URL and a non-empty secret".to_string(),
+                webhook: None,
+            }),
+        );
+    }
+
+    let webhook = Webhook {
+        id: format!("{:016x}", rand::random::<u64>()),
+        url: payload.url,
+        secret: payload.secret,
+        events: payload.events,
+        created_at: Utc::now(),
+    };
+    state
+        .webhooks
+        .lock()
+        .unwrap()
+        .insert(webhook.id.clone(), webhook.clone());
+
+    (
+        StatusCode::CREATED,
+        Json(WebhookResponse {
+            success: true,
+            message: "Webhook registered".to_string(),
+            webhook: Some(webhook),
+        }),
+    )
+}
+
+pub async fn list_webhooks_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let mut webhooks: Vec<Webhook> = state.webhooks.lock().unwrap().values().cloned().collect();
+    webhooks.sort_by_key(|webhook| webhook.created_at);
+
+    Json(WebhooksResponse {
+        success: true,
+        message: format!("{} webhooks registered", webhooks.len()),
+        webhooks,
+    })
+}
+
+pub async fn delete_webhook_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.webhooks.lock().unwrap().remove(&id) {
+        Some(webhook) => (
+            StatusCode::OK,
+            Json(WebhookResponse {
+                success: true,
+                message: "Webhook removed".to_string(),
+                webhook: Some(webhook),
+            }),
+        ),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(WebhookResponse {
+                success: false,
+                message: format!("Webhook {} not found", id),
+                webhook: None,
+            }),
+        ),
+    }
+}
+
+pub async fn dead_letters_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let dead_letters: Vec<DeadLetter> =
+        state.dead_letters.lock().unwrap().iter().cloned().collect();
+
+    Json(DeadLettersResponse {
+        success: true,
+        message: format!("{} failed deliveries", dead_letters.len()),
+        dead_letters,
+    })
+}